@@ -11,5 +11,5 @@ python -m pip install --upgrade pip | Out-Null
 python -m pip install -r (Join-Path $root "requirements.txt") | Out-Null
 python -m pip install pyinstaller | Out-Null
 
-python -m PyInstaller --onefile --name packetlens-sidecar (Join-Path $root "proxy_service.py") --distpath $dist --workpath (Join-Path $root "build") --specpath $root | Out-Null
+python -m PyInstaller --onefile --name packetlens-sidecar --collect-all mitmproxy_windows (Join-Path $root "proxy_service.py") --distpath $dist --workpath (Join-Path $root "build") --specpath $root | Out-Null
 Write-Output "Sidecar built at $dist\\packetlens-sidecar.exe"
@@ -1,19 +1,81 @@
 import argparse
 import asyncio
+import base64
+import copy
+import fnmatch
 import gzip
+import hashlib
+import itertools
+import ipaddress
 import json
+import mimetypes
+import os
+import platform
 import queue
+import re
+import secrets
 import socket
+import ssl
+import sys
 import threading
 import time
+import urllib.error
+import urllib.request
 import zlib
+from collections import OrderedDict
 from datetime import datetime, timezone
+from http.client import HTTPSConnection
+from urllib.parse import parse_qsl, unquote, urlencode, urljoin, urlsplit, urlunsplit
 
-from mitmproxy import http, options
+from mitmproxy import http, options, version
+from mitmproxy.proxy import layers
 from mitmproxy.tools.dump import DumpMaster
 
 
 MAX_BODY_CAPTURE = 100 * 1024
+MAX_STORED_FLOWS = 5000
+MAX_RETAINED_BODY_BYTES = 64 * 1024 * 1024
+MULTIPART_PREVIEW_BYTES = 512
+MAX_HEXDUMP_BYTES = 64 * 1024
+# How long a "timeout" fault holds a request before killing it.
+FAULT_TIMEOUT_SECONDS = 30
+FAULT_KINDS = {"reset", "timeout", "http_500", "http_503"}
+REPLAY_TIMEOUT_SECONDS = 30
+IDLE_HINT_SECONDS = 20
+MAX_PENDING_REDIRECTS = 500
+REDACTED = "[REDACTED]"
+# DNS-over-HTTPS resolvers; a CONNECT to one of these means name lookups skip the proxy.
+DOH_HOSTS = {
+    "dns.google",
+    "dns.google.com",
+    "cloudflare-dns.com",
+    "mozilla.cloudflare-dns.com",
+    "chrome.cloudflare-dns.com",
+    "dns.quad9.net",
+    "doh.opendns.com",
+    "dns.nextdns.io",
+    "doh.cleanbrowsing.org",
+}
+MITMPROXY_CA_PEM = os.path.join(os.path.expanduser("~"), ".mitmproxy", "mitmproxy-ca-cert.pem")
+# Headers urllib manages itself when a request is re-issued.
+REPLAY_SKIPPED_HEADERS = {
+    "host",
+    "content-length",
+    "connection",
+    "proxy-connection",
+    "keep-alive",
+    "transfer-encoding",
+}
+# Tags multi-environment replays; stripped before forwarding and stored as replay_target.
+REPLAY_TARGET_HEADER = "x-packetlens-replay-target"
+# Carries the original flow id on a replay so the new flow can link back to it.
+REPLAY_OF_HEADER = "x-packetlens-replay-of"
+# Carries a key into CaptureState.replay_edits for flows sent by edit-and-replay.
+REPLAY_EDIT_HEADER = "x-packetlens-replay-edit"
+LOOPBACK_HOST = "127.0.0.1"
+# Mirrors open_browser's --proxy-bypass-list.
+PAC_BYPASS_HOSTS = ("localhost", "127.0.0.1", "::1")
+ALL_INTERFACES_HOST = "0.0.0.0"
 TEXTUAL_CONTENT_HINTS = (
     "text/",
     "application/json",
@@ -35,6 +97,9 @@ BINARY_CONTENT_HINTS = (
     "video/",
     "font/",
 )
+PATH_PARAM_SEGMENT = re.compile(
+    r"^(\d+|[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12})$"
+)
 
 
 def _truncate_bytes(data, limit=MAX_BODY_CAPTURE):
@@ -70,6 +135,14 @@ def _get_header_value(headers, name):
     return ""
 
 
+def _header_list_value(header_list, name):
+    target = name.lower()
+    for header in header_list or []:
+        if str(header.get("name", "")).lower() == target:
+            return str(header.get("value", ""))
+    return ""
+
+
 def _looks_binary_from_text(text):
     if not text:
         return False
@@ -80,7 +153,7 @@ def _looks_binary_from_text(text):
     return ratio > 0.08
 
 
-def _decode_for_display(data, headers):
+def _decode_for_display(data, headers, decode_brotli=True):
     if not data:
         return ""
 
@@ -96,7 +169,7 @@ def _decode_for_display(data, headers):
             decoded_bytes = gzip.decompress(data)
         elif "deflate" in content_encoding:
             decoded_bytes = zlib.decompress(data)
-        elif "br" in content_encoding:
+        elif "br" in content_encoding and decode_brotli:
             try:
                 import brotli  # optional dependency at runtime
 
@@ -116,6 +189,211 @@ def _decode_for_display(data, headers):
     return text
 
 
+def _captured_body(data, headers, enabled, decode_brotli=True, limit=MAX_BODY_CAPTURE):
+    if not enabled:
+        return "", bool(data)
+    return _decode_for_display(_truncate_bytes(data, limit), headers, decode_brotli), len(data) > limit
+
+
+def _body_hash(data):
+    return hashlib.sha256(data).hexdigest() if data else None
+
+
+def _length_mismatch(headers, wire_size):
+    declared = _get_header_value(headers, "content-length").strip()
+    if not declared.isdigit():
+        return None
+    return int(declared) != wire_size
+
+
+def _content_length_mismatch(req, resp):
+    """Compares declared Content-Length with the bytes actually received; None when nothing is declared."""
+    checks = [_length_mismatch(req.headers, len(req.raw_content or b""))]
+    # HEAD, 1xx, 204 and 304 responses may declare a length without carrying a body.
+    has_body = resp is not None and resp.status_code >= 200 and resp.status_code not in (204, 304)
+    if has_body and req.method.upper() != "HEAD":
+        checks.append(_length_mismatch(resp.headers, len(resp.raw_content or b"")))
+    checks = [c for c in checks if c is not None]
+    return any(checks) if checks else None
+
+
+def _is_preflight(req):
+    # CORS preflights are always OPTIONS, so plain OPTIONS probes are hidden with them.
+    return req.method.upper() == "OPTIONS"
+
+
+def _is_grease(value):
+    return value & 0x0F0F == 0x0A0A and value >> 8 == value & 0xFF
+
+
+def _u16_list(data, length_bytes):
+    body = data[length_bytes:]
+    return [int.from_bytes(body[i : i + 2], "big") for i in range(0, len(body) - 1, 2)]
+
+
+def _tls_fingerprints(client_hello):
+    """Computes (ja3, ja4) from a mitmproxy ClientHello."""
+    raw = client_hello.raw_bytes(wrap_in_record=False)
+    legacy_version = int.from_bytes(raw[:2], "big")
+    ciphers = [c for c in client_hello.cipher_suites if not _is_grease(c)]
+    extensions = [(t, data) for t, data in client_hello.extensions if not _is_grease(t)]
+    ext_data = dict(extensions)
+    ext_types = [t for t, _ in extensions]
+    curves = [c for c in _u16_list(ext_data.get(10, b""), 2) if not _is_grease(c)]
+    point_formats = list(ext_data.get(11, b"")[1:])
+    sig_algs = _u16_list(ext_data.get(13, b""), 2)
+    versions = [v for v in _u16_list(ext_data.get(43, b""), 1) if not _is_grease(v)]
+
+    ja3_text = ",".join(
+        [
+            str(legacy_version),
+            "-".join(str(c) for c in ciphers),
+            "-".join(str(t) for t in ext_types),
+            "-".join(str(c) for c in curves),
+            "-".join(str(p) for p in point_formats),
+        ]
+    )
+    ja3 = hashlib.md5(ja3_text.encode("ascii")).hexdigest()
+
+    version = max(versions) if versions else legacy_version
+    version_code = {0x0304: "13", 0x0303: "12", 0x0302: "11", 0x0301: "10", 0x0300: "s3"}.get(version, "00")
+    alpn = client_hello.alpn_protocols[0].decode("latin-1") if client_hello.alpn_protocols else ""
+    alpn_code = f"{alpn[0]}{alpn[-1]}" if alpn else "00"
+    prefix = (
+        f"t{version_code}{'d' if client_hello.sni else 'i'}"
+        f"{min(len(ciphers), 99):02d}{min(len(ext_types), 99):02d}{alpn_code}"
+    )
+
+    def digest(text):
+        return hashlib.sha256(text.encode("ascii")).hexdigest()[:12] if text else "000000000000"
+
+    cipher_part = digest(",".join(sorted(f"{c:04x}" for c in ciphers)))
+    ext_text = ",".join(sorted(f"{t:04x}" for t in ext_types if t not in (0x0000, 0x0010)))
+    if ext_text and sig_algs:
+        ext_text += "_" + ",".join(f"{a:04x}" for a in sig_algs)
+    return ja3, f"{prefix}_{cipher_part}_{digest(ext_text)}"
+
+
+def _header_param(header_value, param):
+    for piece in header_value.split(";")[1:]:
+        key, _, value = piece.strip().partition("=")
+        if key.strip().lower() == param:
+            return value.strip().strip('"')
+    return None
+
+
+def _parse_multipart(data, headers):
+    content_type = _get_header_value(headers, "content-type")
+    if not data or not content_type.lower().startswith("multipart/form-data"):
+        return None
+    boundary = _header_param(content_type, "boundary")
+    if not boundary:
+        return None
+
+    parts = []
+    delimiter = b"--" + boundary.encode("latin-1")
+    for chunk in data.split(delimiter)[1:]:
+        if chunk.startswith(b"--"):
+            break
+        chunk = chunk[2:] if chunk.startswith(b"\r\n") else chunk
+        raw_headers, sep, body = chunk.partition(b"\r\n\r\n")
+        if not sep:
+            continue
+        if body.endswith(b"\r\n"):
+            body = body[:-2]
+
+        part_headers = {}
+        for line in raw_headers.decode("latin-1").split("\r\n"):
+            key, _, value = line.partition(":")
+            if key:
+                part_headers[key.strip().lower()] = value.strip()
+        disposition = part_headers.get("content-disposition", "")
+        part_type = part_headers.get("content-type")
+
+        preview, had_decode_issue = _safe_decode(body[:MULTIPART_PREVIEW_BYTES])
+        if had_decode_issue or _looks_binary_from_text(preview):
+            preview = f"[binary part omitted: {part_type or 'binary/unknown'}; {len(body)} bytes]"
+
+        parts.append(
+            {
+                "name": _header_param(disposition, "name") or "",
+                "filename": _header_param(disposition, "filename"),
+                "content_type": part_type,
+                "size": len(body),
+                "preview": preview,
+            }
+        )
+    return parts
+
+
+def _compress_for_client(body, accept_encoding):
+    """Encode a locally supplied body with the best encoding the client accepts."""
+    accepted = [token.split(";")[0].strip().lower() for token in (accept_encoding or "").split(",")]
+    if "br" in accepted:
+        try:
+            import brotli  # optional dependency at runtime
+
+            return brotli.compress(body), "br"
+        except Exception:
+            pass
+    if "gzip" in accepted:
+        return gzip.compress(body), "gzip"
+    if "deflate" in accepted:
+        return zlib.compress(body), "deflate"
+    return body, None
+
+
+def _local_response(status, body, headers, compress=False, accept_encoding=""):
+    """Builds a response served by the sidecar itself, optionally compressed for the client."""
+    response = http.Response.make(status, body, headers)
+    if compress:
+        encoded, encoding = _compress_for_client(body, accept_encoding)
+        if encoding:
+            response.headers["content-encoding"] = encoding
+            response.raw_content = encoded
+            response.headers["content-length"] = str(len(encoded))
+    return response
+
+
+def _map_local_file(local_path, request_path):
+    """Resolves the file a map-local rule serves; folders map the request path beneath them."""
+    if not os.path.isdir(local_path):
+        return local_path
+    root = os.path.abspath(local_path)
+    target = os.path.abspath(os.path.join(root, unquote(urlsplit(request_path).path).lstrip("/")))
+    if os.path.commonpath([root, target]) != root:
+        raise ValueError(f"{request_path} is outside {root}")
+    if os.path.isdir(target):
+        target = os.path.join(target, "index.html")
+    return target
+
+
+def _cert_name(name):
+    return ", ".join(f"{key}={value}" for key, value in name) or None
+
+
+def _tls_details(server):
+    """Negotiated parameters and leaf certificate of an upstream TLS connection, or None."""
+    if not server or not server.tls_established:
+        return None
+    leaf = server.certificate_list[0] if server.certificate_list else None
+    return {
+        "sni": server.sni,
+        "alpn": server.alpn.decode("latin-1") if server.alpn else None,
+        "tls_version": server.tls_version,
+        "cipher": server.cipher,
+        "cert_subject": _cert_name(leaf.subject) if leaf else None,
+        "cert_issuer": _cert_name(leaf.issuer) if leaf else None,
+        "cert_not_after": leaf.notafter.timestamp() if leaf else None,
+    }
+
+
+def _certificate_chain(server):
+    if not server or not server.tls_established:
+        return []
+    return [cert.to_pem().decode("ascii") for cert in server.certificate_list]
+
+
 def _headers_to_list(headers):
     try:
         items = headers.items(multi=True)
@@ -139,6 +417,222 @@ def _wait_for_port(host, port, timeout=5.0):
     return False
 
 
+def _bind_host(bind_scope):
+    return ALL_INTERFACES_HOST if str(bind_scope or "").lower() == "all" else LOOPBACK_HOST
+
+
+def _lan_address():
+    # Connecting a UDP socket sends nothing; it only picks the outbound interface.
+    try:
+        with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+            sock.connect(("192.0.2.1", 9))
+            return sock.getsockname()[0]
+    except OSError:
+        return LOOPBACK_HOST
+
+
+def _lan_addresses():
+    """Every IPv4 address of this machine that another device on the LAN could reach."""
+    import psutil
+
+    addresses = []
+    for interface_addresses in psutil.net_if_addrs().values():
+        for address in interface_addresses:
+            if address.family != socket.AF_INET:
+                continue
+            ip = ipaddress.ip_address(address.address)
+            if ip.is_loopback or ip.is_link_local or address.address in addresses:
+                continue
+            addresses.append(address.address)
+    return addresses
+
+
+def _matches_pattern(value, pattern):
+    value = (value or "").lower()
+    pattern = (pattern or "").strip().lower()
+    if not pattern:
+        return False
+    if any(ch in pattern for ch in "*?["):
+        return fnmatch.fnmatch(value, pattern)
+    return pattern in value
+
+
+def _ephemeral_port(host):
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as sock:
+        sock.bind((host, 0))
+        return sock.getsockname()[1]
+
+
+def _parse_dns_server(text):
+    text = text.strip()
+    host, port = text, 53
+    if text.startswith("["):
+        host, _, rest = text[1:].partition("]")
+        port = int(rest[1:]) if rest.startswith(":") else 53
+    elif text.count(":") == 1:
+        host, _, port_text = text.partition(":")
+        port = int(port_text)
+    return str(ipaddress.ip_address(host)), port
+
+
+def _skip_dns_name(data, offset):
+    while True:
+        length = data[offset]
+        if length & 0xC0 == 0xC0:
+            return offset + 2
+        offset += 1 + length
+        if length == 0:
+            return offset
+
+
+def _dns_lookup(server, host, timeout=2.0):
+    """Resolves an A record for host with a single UDP query to server."""
+    query_id = secrets.randbits(16)
+    question = b"".join(bytes([len(label)]) + label.encode("idna") for label in host.rstrip(".").split("."))
+    packet = query_id.to_bytes(2, "big") + b"\x01\x00\x00\x01\x00\x00\x00\x00\x00\x00"
+    packet += question + b"\x00\x00\x01\x00\x01"
+    family = socket.AF_INET6 if ":" in server[0] else socket.AF_INET
+    with socket.socket(family, socket.SOCK_DGRAM) as sock:
+        sock.settimeout(timeout)
+        sock.sendto(packet, server)
+        data = sock.recv(4096)
+    if int.from_bytes(data[:2], "big") != query_id:
+        raise OSError("mismatched DNS response")
+    rcode = data[3] & 0x0F
+    if rcode:
+        raise OSError(f"DNS server returned rcode {rcode}")
+    answers = int.from_bytes(data[6:8], "big")
+    offset = _skip_dns_name(data, 12) + 4
+    for _ in range(answers):
+        offset = _skip_dns_name(data, offset)
+        rtype = int.from_bytes(data[offset : offset + 2], "big")
+        length = int.from_bytes(data[offset + 8 : offset + 10], "big")
+        offset += 10
+        if rtype == 1 and length == 4:
+            return socket.inet_ntoa(data[offset : offset + 4])
+        offset += length
+    raise OSError("no A record")
+
+
+def _load_proto_pool(path, pool=None):
+    """Adds the files of a compiled descriptor set to pool, or to a new pool when None, and returns it."""
+    from google.protobuf import descriptor_pb2, descriptor_pool  # optional dependency at runtime
+
+    with open(path, "rb") as handle:
+        descriptor_set = descriptor_pb2.FileDescriptorSet.FromString(handle.read())
+    if pool is None:
+        pool = descriptor_pool.DescriptorPool()
+    # Sets compiled separately often repeat shared imports such as google/protobuf/*.proto.
+    for file_proto in descriptor_set.file:
+        try:
+            pool.FindFileByName(file_proto.name)
+        except KeyError:
+            pool.Add(file_proto)
+    return pool
+
+
+def _grpc_messages(data, encoding):
+    messages = []
+    offset = 0
+    while offset + 5 <= len(data):
+        compressed = data[offset]
+        length = int.from_bytes(data[offset + 1 : offset + 5], "big")
+        payload = data[offset + 5 : offset + 5 + length]
+        if len(payload) < length:
+            raise ValueError("truncated gRPC frame")
+        if compressed:
+            if encoding != "gzip":
+                raise ValueError(f"unsupported grpc-encoding {encoding or 'none'}")
+            payload = gzip.decompress(payload)
+        messages.append(payload)
+        offset += 5 + length
+    return messages
+
+
+def _grpc_method(pool, url):
+    service_name, _, method_name = (urlsplit(url).path or "").strip("/").rpartition("/")
+    try:
+        return pool.FindServiceByName(service_name).FindMethodByName(method_name)
+    except KeyError:
+        raise ValueError(f"no gRPC method {service_name}/{method_name} in the descriptor") from None
+
+
+def _decode_grpc_flow(pool, record, request_body, response_body):
+    """Lists the messages of a gRPC exchange, decoded to JSON when the pool knows the method."""
+    method = None
+    if pool is not None:
+        try:
+            method = _grpc_method(pool, record.get("url", ""))
+        except ValueError:
+            method = None
+
+    def messages(data, headers, descriptor):
+        encoding = _header_list_value(headers or [], "grpc-encoding").lower()
+        try:
+            payloads = _grpc_messages(data or b"", encoding)
+        except (ValueError, OSError, EOFError) as exc:
+            return [{"size": len(data or b""), "json": None, "error": str(exc)}]
+        decoded = []
+        for payload in payloads:
+            message = {"size": len(payload), "json": None, "error": None}
+            if descriptor is not None:
+                try:
+                    from google.protobuf import json_format, message_factory
+
+                    message_class = message_factory.GetMessageClass(descriptor)
+                    message["json"] = json_format.MessageToDict(message_class.FromString(payload))
+                except Exception as exc:
+                    message["error"] = str(exc)
+            decoded.append(message)
+        return decoded
+
+    return {
+        "method": method.full_name if method else None,
+        "request": messages(request_body, record.get("request_headers"), method and method.input_type),
+        "response": messages(response_body, record.get("response_headers"), method and method.output_type),
+    }
+
+
+def _hexdump_lines(data, offset):
+    """Formats data as offset / 16 hex bytes / ASCII rows, numbered from offset."""
+    lines = []
+    for start in range(0, len(data), 16):
+        chunk = data[start : start + 16]
+        hex_part = " ".join(f"{b:02x}" for b in chunk[:8]) + "  " + " ".join(f"{b:02x}" for b in chunk[8:])
+        ascii_part = "".join(chr(b) if 32 <= b < 127 else "." for b in chunk)
+        lines.append(f"{offset + start:08x}  {hex_part:<48}  |{ascii_part}|")
+    return lines
+
+
+def _client_process(peername):
+    """(pid, executable name, command line) of the process owning a proxy client's socket, or None."""
+    import psutil
+
+    for conn in psutil.net_connections(kind="tcp"):
+        if conn.pid and conn.laddr and tuple(conn.laddr[:2]) == tuple(peername[:2]):
+            try:
+                process = psutil.Process(conn.pid)
+                return conn.pid, process.name(), process.cmdline()
+            except psutil.Error:
+                return conn.pid, "", []
+    return None
+
+
+def _process_rss_bytes():
+    try:
+        import psutil  # optional dependency at runtime
+
+        return psutil.Process().memory_info().rss
+    except Exception:
+        pass
+    try:
+        with open("/proc/self/statm", "r", encoding="ascii") as handle:
+            resident_pages = int(handle.read().split()[1])
+        return resident_pages * os.sysconf("SC_PAGE_SIZE")
+    except Exception:
+        return 0
+
+
 def _port_in_use(host, port):
     try:
         with socket.create_connection((host, port), timeout=0.2):
@@ -152,18 +646,891 @@ class CaptureState:
         self.capture_enabled = threading.Event()
         self.capture_enabled.set()
         self.paused = threading.Event()
+        self.passthrough = threading.Event()
+        self.muted_hosts = set()
+        self.capture_request_body = True
+        self.capture_response_body = True
+        # Bytes of each body inlined into flow events vs. retained in full for save and export.
+        self.stream_body_limit = MAX_BODY_CAPTURE
+        self.store_body_limit = MAX_RETAINED_BODY_BYTES
+        self.sni_overrides = {}
+        self.request_timeout = 0
+        self.resolve_timings = False
+        self.show_preflight = True
+        self.strip_accept_encoding = False
+        self.connection_reuse = True
+        self.brotli_decode = True
+        # Host patterns whose upstream TLS failures are reported; every HTTPS flow records its handshake.
+        self.handshake_hosts = set()
+        # Client peername of a running handshake replay -> the recorded handshake its upstream connect uses.
+        self.handshake_pins = {}
+        self.user_agent = None
+        self.pause_on_error = False
+        # With pause_on_error, statuses at or above this also pause; None means transport errors only.
+        self.pause_status_min = None
+        # Non-zero while record-only mode answers every request locally with this status.
+        self.record_only_status = 0
+        # Host pattern -> (down_kbps, latency_ms); either may be None.
+        self.host_throttles = {}
+        # (down_kbps, up_kbps, latency_ms) applied to every host without its own throttle; 0 is unlimited.
+        self.network_throttle = (0, 0, 0)
+        # Upstream status code -> extra latency in ms added before the response reaches the client.
+        self.status_latency = {}
+        # (ip, port) resolvers used for upstream connects; empty means the system resolver.
+        self.dns_servers = []
+        # Correlation token from open_browser; only that browser's connections are captured.
+        self.client_scope_token = None
+        self.scoped_clients = set()
+        # (pids, lowercase names without .exe) allowed by the process filter, or None for all.
+        self.process_filter = None
+        self.process_clients = set()
+        self.capture_tls_fingerprints = False
+        # Client connection id -> (ja3, ja4), filled from each ClientHello.
+        self.client_fingerprints = {}
+        self.capture_client_hello = False
+        # Client connection id -> base64 of the raw ClientHello record.
+        self.client_hellos = {}
+        # Destination ports to intercept; empty means every port.
+        self.allowed_ports = set()
+        # Host patterns whose upstream connections only offer HTTP/1.1 via ALPN.
+        self.http1_hosts = set()
+        # "first" or "last" keeps one flow per endpoint template; None captures every occurrence.
+        self.dedupe_keep = None
+        # Endpoint key -> id of the flow currently kept for it.
+        self.endpoint_flows = {}
+        # (token_header, refresh_flow_id, json_path) re-issued before each replay run; None when unset.
+        self.auth_refresh = None
+        # Edit token -> request edits attached to the flow an edit-and-replay produces.
+        self.replay_edits = {}
+        # (url_pattern, upper-case method or None) rules that hold matching requests.
+        self.breakpoints = []
+        # Mock, map-local and map-remote rules as dicts; the first matching rule wins.
+        self.mock_rules = []
+        # Protobuf descriptor pool for decoding gRPC messages; None until descriptors are loaded.
+        self.proto_pool = None
+        # (url_pattern, fault kind) pairs; the first match fails the request.
+        self.fault_rules = []
+        self.map_local_rules = []
+        self.map_remote_rules = []
+        # Flow id -> (event loop, future) for requests held by a breakpoint.
+        self.intercepted = {}
+
+    def is_port_allowed(self, port):
+        return not self.allowed_ports or port in self.allowed_ports
+
+    def throttle_for(self, host):
+        for pattern, limits in list(self.host_throttles.items()):
+            if _matches_pattern(host, pattern):
+                return limits
+        return None, None
+
+    def fault_for(self, url):
+        return next((fault for pattern, fault in list(self.fault_rules) if _matches_pattern(url, pattern)), None)
+
+    def mock_for(self, request):
+        return next(
+            (
+                r
+                for r in list(self.mock_rules)
+                if _matches_pattern(request.url, r["url_pattern"])
+                and (r["method"] is None or request.method.upper() == r["method"])
+            ),
+            None,
+        )
+
+    def map_local_for(self, url):
+        return next((r for r in list(self.map_local_rules) if _matches_pattern(url, r["url_pattern"])), None)
+
+    def map_remote_for(self, url):
+        return next((r for r in list(self.map_remote_rules) if _matches_pattern(url, r["url_pattern"])), None)
+
+    def breakpoint_hit(self, request):
+        return any(
+            _matches_pattern(request.url, pattern) and (method is None or request.method.upper() == method)
+            for pattern, method in list(self.breakpoints)
+        )
+
+    def forces_http1(self, host):
+        return any(_matches_pattern(host, pattern) for pattern in list(self.http1_hosts))
+
+    def is_muted(self, host):
+        return any(_matches_pattern(host, pattern) for pattern in list(self.muted_hosts))
 
     def should_capture(self):
-        return self.capture_enabled.is_set() and not self.paused.is_set()
+        return (
+            self.capture_enabled.is_set()
+            and not self.paused.is_set()
+            and not self.passthrough.is_set()
+        )
+
+
+def _validate_output_path(path):
+    if not path or not str(path).strip():
+        raise ValueError("Output path is empty.")
+    parent = os.path.dirname(os.path.abspath(path))
+    if not os.path.isdir(parent):
+        raise ValueError(f"Output folder does not exist: {parent}")
+    return os.path.abspath(path)
+
+
+def _retarget_url(url, base_url):
+    if not base_url:
+        return url
+    original = urlsplit(url)
+    base = urlsplit(base_url)
+    path = base.path.rstrip("/") + (original.path or "/")
+    return urlunsplit(
+        (base.scheme or original.scheme, base.netloc or original.netloc, path, original.query, original.fragment)
+    )
+
+
+def _replace_pair(pairs, name, value):
+    if not any(k == name for k, _ in pairs):
+        raise ValueError(f"{name} not present")
+    return [(k, value if k == name else v) for k, v in pairs]
+
+
+def _mutate_request(record, field, value):
+    """Returns (url, headers, body) with only the named field replaced.
+
+    `field` is `header:<name>`, `query:<name>` or `body:<key>`; JSON body keys may be dotted paths.
+    """
+    kind, _, name = field.partition(":")
+    if not name:
+        raise ValueError("field must look like header:<name>, query:<name> or body:<key>")
+    url = record.get("url", "")
+    headers = [dict(h) for h in record.get("request_headers") or []]
+    body = record.get("request_body") or ""
+    if kind == "header":
+        matches = [h for h in headers if h.get("name", "").lower() == name.lower()]
+        if not matches:
+            raise ValueError(f"header {name} not present")
+        for h in matches:
+            h["value"] = value
+    elif kind == "query":
+        parts = urlsplit(url)
+        query = _replace_pair(parse_qsl(parts.query, keep_blank_values=True), name, value)
+        url = urlunsplit(parts._replace(query=urlencode(query)))
+    elif kind == "body":
+        if record.get("request_body_truncated"):
+            raise ValueError("request body was truncated at capture")
+        try:
+            data = json.loads(body)
+        except ValueError:
+            body = urlencode(_replace_pair(parse_qsl(body, keep_blank_values=True), name, value))
+        else:
+            *path, leaf = name.split(".")
+            target = data
+            for key in path:
+                target = target[int(key)] if isinstance(target, list) else target[key]
+            if isinstance(target, list):
+                target[int(leaf)] = value
+            elif isinstance(target, dict) and leaf in target:
+                target[leaf] = value
+            else:
+                raise ValueError(f"body key {name} not present")
+            body = json.dumps(data)
+    else:
+        raise ValueError(f"unknown field kind {kind}")
+    return url, headers, body
+
+
+def _parse_raw_request(raw_request, scheme, host, port):
+    """Splits a pasted HTTP/1 request into (method, url, headers, body)."""
+    head, _, body = raw_request.replace("\r\n", "\n").partition("\n\n")
+    lines = head.lstrip("\n").split("\n")
+    parts = lines[0].split()
+    if len(parts) < 2 or not parts[0].isalpha():
+        raise ValueError(f"bad request line: {lines[0][:80]!r}")
+    method, target = parts[0].upper(), parts[1]
+    if scheme not in ("http", "https"):
+        raise ValueError(f"unsupported scheme {scheme!r}")
+    if not host or not 0 < port < 65536:
+        raise ValueError("host and port are required")
+    headers = []
+    for line in lines[1:]:
+        name, sep, value = line.partition(":")
+        if not sep or not name.strip():
+            raise ValueError(f"bad header line: {line[:80]!r}")
+        headers.append({"name": name.strip(), "value": value.strip()})
+    if not target.startswith("/"):
+        target = urlunsplit(("", "", *urlsplit(target)[2:])) or "/"
+    return method, f"{scheme}://{host}:{port}{target}", headers, body
+
+
+def _json_path_value(data, path):
+    """Follows a dotted path such as `data.token` or `$.items[0].id` into parsed JSON."""
+    keys = [key for key in re.split(r"[.\[\]]", path.strip().lstrip("$")) if key]
+    if not keys:
+        raise ValueError("JSON path is empty")
+    value = data
+    for key in keys:
+        try:
+            value = value[int(key)] if isinstance(value, list) else value[key]
+        except (KeyError, IndexError, TypeError, ValueError):
+            raise ValueError(f"{path} not found in refresh response") from None
+    if isinstance(value, (dict, list)) or value is None:
+        raise ValueError(f"{path} does not point to a scalar value")
+    return str(value)
+
+
+def _with_header(headers, name, value):
+    kept = [dict(h) for h in headers or [] if h.get("name", "").lower() != name.lower()]
+    return kept + [{"name": name, "value": value}]
+
+
+def _request_edits(original, method, url, headers, body):
+    """Lists what an edited request changes relative to the captured one, header by header."""
+    edits = []
+    for field, before, after in (("method", original.get("method"), method), ("url", original.get("url"), url)):
+        if before != after:
+            edits.append({"field": field, "original": before, "modified": after})
+
+    def by_name(items):
+        values = {}
+        for name, value in _header_items(items):
+            values.setdefault(name.lower(), []).append(value)
+        return {name: ", ".join(value) for name, value in values.items()}
+
+    before_headers = by_name(original.get("request_headers"))
+    after_headers = by_name(headers)
+    for name in sorted(set(before_headers) | set(after_headers)):
+        if before_headers.get(name) != after_headers.get(name):
+            edits.append(
+                {"field": f"header:{name}", "original": before_headers.get(name), "modified": after_headers.get(name)}
+            )
+    before_body = original.get("request_body") or ""
+    if before_body != body:
+        edits.append(
+            {"field": "body", "original": f"{len(before_body)} chars", "modified": f"{len(body)} chars"}
+        )
+    return edits
+
+
+def _original_host(url, preserve_host):
+    return urlsplit(url).netloc if preserve_host else None
+
+
+def _header_items(headers):
+    if not headers:
+        return []
+    if isinstance(headers, dict):
+        return [(str(k), str(v)) for k, v in headers.items()]
+    return [(str(h.get("name", "")), str(h.get("value", ""))) for h in headers if h.get("name")]
+
+
+class _NoRedirect(urllib.request.HTTPRedirectHandler):
+    def redirect_request(self, req, fp, code, msg, headers, newurl):
+        return None
+
+
+class _BoundHTTPSHandler(urllib.request.HTTPSHandler):
+    """Connects to the proxy from a fixed local port, so the proxy can tell which client connection is ours."""
+
+    def __init__(self, context, source_port):
+        super().__init__(context=context)
+        self.source_address = (LOOPBACK_HOST, source_port)
+
+    def https_open(self, req):
+        return self.do_open(HTTPSConnection, req, context=self._context, source_address=self.source_address)
+
+
+class ProxyClient:
+    """Issues requests through the running proxy so they are captured like any other flow."""
+
+    def __init__(self, service):
+        self.service = service
+
+    def _opener(self, direct=False, source_port=None):
+        if direct:
+            return urllib.request.build_opener(urllib.request.ProxyHandler({}), _NoRedirect())
+        port = self.service.current_port
+        if not port:
+            raise RuntimeError("Proxy is not running. Start Capture first.")
+        proxy = f"http://{LOOPBACK_HOST}:{port}"
+        if os.path.exists(MITMPROXY_CA_PEM):
+            context = ssl.create_default_context(cafile=MITMPROXY_CA_PEM)
+        else:
+            context = ssl._create_unverified_context()
+        if source_port:
+            https_handler = _BoundHTTPSHandler(context, source_port)
+        else:
+            https_handler = urllib.request.HTTPSHandler(context=context)
+        return urllib.request.build_opener(
+            urllib.request.ProxyHandler({"http": proxy, "https": proxy}), https_handler, _NoRedirect()
+        )
+
+    def send(
+        self, method, url, headers=None, body=b"", timeout=REPLAY_TIMEOUT_SECONDS, host_header=None, source_port=None
+    ):
+        """source_port fixes the local port of an HTTPS request's connection to the proxy."""
+        return self.exchange(method, url, headers, body, timeout, host_header, source_port=source_port)[0]
+
+    def fetch(self, method, url, headers=None, body=b"", timeout=REPLAY_TIMEOUT_SECONDS, host_header=None):
+        """Like send, but returns (status, response body)."""
+        status, _, response_body = self.exchange(method, url, headers, body, timeout, host_header)
+        return status, response_body
+
+    def exchange(
+        self,
+        method,
+        url,
+        headers=None,
+        body=b"",
+        timeout=REPLAY_TIMEOUT_SECONDS,
+        host_header=None,
+        direct=False,
+        source_port=None,
+    ):
+        """Returns (status, response headers, response body); direct bypasses the proxy entirely."""
+        if isinstance(body, str):
+            body = body.encode("utf-8")
+        request = urllib.request.Request(url, data=body or None, method=(method or "GET").upper())
+        for name, value in _header_items(headers):
+            if name.lower() not in REPLAY_SKIPPED_HEADERS:
+                request.add_header(name, value)
+        if host_header:
+            # An explicit Host stops urllib from deriving it from the (retargeted) URL.
+            request.add_header("Host", host_header)
+        try:
+            with self._opener(direct, source_port).open(request, timeout=timeout) as response:
+                return response.status, response.headers, response.read()
+        except urllib.error.HTTPError as exc:
+            return exc.code, exc.headers, exc.read()
+
+
+def _composed_record(method, url, headers, body, started, status, response_headers, response_body, error):
+    """Builds a flow record for a composer request that went straight to the server."""
+    parts = urlsplit(url)
+    request_headers = [{"name": name, "value": value} for name, value in _header_items(headers)]
+    response_list = _headers_to_list(response_headers) if response_headers is not None else None
+    ended = time.time()
+    request_text, request_truncated = _captured_body(body, dict(_header_items(headers)), True)
+    response_text, response_truncated = _captured_body(response_body, response_headers, True)
+    return {
+        "id": f"composer-{secrets.token_hex(8)}",
+        "started": started,
+        "ended": ended,
+        "duration_ms": max(0, int((ended - started) * 1000)),
+        "method": method,
+        "url": url,
+        "host": parts.hostname or "",
+        "path": urlunsplit(("", "", parts.path or "/", parts.query, "")),
+        "scheme": parts.scheme,
+        "server_port": parts.port or (443 if parts.scheme == "https" else 80),
+        "status_code": status,
+        "request_headers": request_headers,
+        "response_headers": response_list,
+        "request_body_size": len(body),
+        "response_body_size": len(response_body),
+        "request_body": request_text,
+        "response_body": response_text,
+        "request_body_truncated": request_truncated,
+        "response_body_truncated": response_truncated,
+        "request_body_hash": _body_hash(body),
+        "response_body_hash": _body_hash(response_body),
+        "error": error,
+        "started_iso": _iso_time(started),
+    }
+
+
+def _elapsed_ms(start, end):
+    if not start or not end:
+        return None
+    return round(max(0.0, end - start) * 1000, 2)
+
+
+def _har_timings(record):
+    timings = record.get("timings") or {}
+    if timings.get("ttfb_ms") is None:
+        return {"send": 0, "wait": record.get("duration_ms", 0), "receive": 0}
+    connect, tls = timings.get("connect_ms"), timings.get("tls_ms")
+    return {
+        "dns": -1 if timings.get("dns_ms") is None else timings["dns_ms"],
+        # HAR counts the TLS handshake inside connect.
+        "connect": -1 if connect is None else connect + (tls or 0),
+        "ssl": -1 if tls is None else tls,
+        "send": timings.get("send_ms") or 0,
+        "wait": timings["ttfb_ms"],
+        "receive": timings.get("download_ms") or 0,
+    }
+
+
+def _har_entry(record):
+    request_headers = record.get("request_headers") or []
+    response_headers = record.get("response_headers") or []
+    request = {
+        "method": record.get("method", ""),
+        "url": record.get("url", ""),
+        "httpVersion": "HTTP/1.1",
+        "headers": request_headers,
+        "queryString": [
+            {"name": k, "value": v} for k, v in parse_qsl(urlsplit(record.get("url", "")).query)
+        ],
+        "cookies": [],
+        "headersSize": -1,
+        "bodySize": record.get("request_body_size", 0),
+    }
+    if record.get("request_body"):
+        request["postData"] = {
+            "mimeType": _header_list_value(request_headers, "content-type"),
+            "text": record["request_body"],
+        }
+    return {
+        "startedDateTime": _iso_time(record.get("started") or 0),
+        "time": record.get("duration_ms", 0),
+        "request": request,
+        "response": {
+            "status": record.get("status_code", 0),
+            "statusText": "",
+            "httpVersion": "HTTP/1.1",
+            "headers": response_headers,
+            "cookies": [],
+            "content": {
+                "size": record.get("response_body_decoded_size") or record.get("response_body_size", 0),
+                "mimeType": _header_list_value(response_headers, "content-type"),
+                "text": record.get("response_body", ""),
+            },
+            "redirectURL": _header_list_value(response_headers, "location"),
+            "headersSize": -1,
+            "bodySize": record.get("response_body_size", 0),
+        },
+        "cache": {},
+        "timings": _har_timings(record),
+        "_error": record.get("error") or None,
+        "_clientOutcome": record.get("client_outcome"),
+    }
+
+
+def _build_har(records, markers=None):
+    return {
+        "log": {
+            "version": "1.2",
+            "creator": {"name": "PacketLens", "version": "1.0"},
+            "entries": [_har_entry(r) for r in records],
+            "_markers": [
+                {"time": _iso_time(m["timestamp"]), "label": m["label"]} for m in markers or []
+            ],
+        }
+    }
+
+
+def _path_template(path):
+    """Collapses numeric/UUID segments into {id}, {id2}, ... params."""
+    segments = []
+    params = []
+    for segment in (path or "/").split("?")[0].split("/"):
+        if PATH_PARAM_SEGMENT.match(segment):
+            name = "id" if not params else f"id{len(params) + 1}"
+            params.append(name)
+            segment = "{" + name + "}"
+        segments.append(segment)
+    return "/".join(segments) or "/", params
+
+
+def _json_schema(value):
+    if isinstance(value, bool):
+        return {"type": "boolean"}
+    if isinstance(value, int):
+        return {"type": "integer"}
+    if isinstance(value, float):
+        return {"type": "number"}
+    if isinstance(value, str):
+        return {"type": "string"}
+    if isinstance(value, list):
+        return {"type": "array", "items": _json_schema(value[0]) if value else {}}
+    if isinstance(value, dict):
+        return {"type": "object", "properties": {k: _json_schema(v) for k, v in value.items()}}
+    return {"nullable": True}
+
+
+def _body_schema(body):
+    if not body:
+        return None
+    try:
+        return _json_schema(json.loads(body))
+    except ValueError:
+        return None
+
+
+def _build_openapi(records):
+    servers = []
+    paths = OrderedDict()
+    for r in records:
+        server = f"{r.get('scheme') or 'https'}://{r.get('host') or 'unknown'}"
+        if server not in servers:
+            servers.append(server)
+        template, params = _path_template(r.get("path"))
+        operation = paths.setdefault(template, OrderedDict()).setdefault(
+            (r.get("method") or "GET").lower(),
+            {
+                "summary": f"{r.get('method') or 'GET'} {template}",
+                "parameters": [
+                    {"name": name, "in": "path", "required": True, "schema": {"type": "string"}}
+                    for name in params
+                ],
+                "responses": {},
+            },
+        )
+        known = {(p["name"], p["in"]) for p in operation["parameters"]}
+        for name, _ in parse_qsl(urlsplit(r.get("url") or "").query, keep_blank_values=True):
+            if (name, "query") not in known:
+                known.add((name, "query"))
+                operation["parameters"].append({"name": name, "in": "query", "schema": {"type": "string"}})
+        request_schema = _body_schema(r.get("request_body"))
+        if request_schema and "requestBody" not in operation:
+            operation["requestBody"] = {"content": {"application/json": {"schema": request_schema}}}
+        status = str(r.get("status_code") or "default")
+        if status not in operation["responses"]:
+            response = {"description": "Observed response"}
+            response_schema = _body_schema(r.get("response_body"))
+            if response_schema:
+                response["content"] = {"application/json": {"schema": response_schema}}
+            operation["responses"][status] = response
+    return {
+        "openapi": "3.0.3",
+        "info": {"title": "PacketLens observed API", "version": datetime.now().strftime("%Y-%m-%d")},
+        "servers": [{"url": url} for url in servers],
+        "paths": paths,
+    }
+
+
+def _load_baseline(path):
+    """Reads a saved flow list (PacketLens JSON) or a HAR file into minimal records."""
+    with open(path, "r", encoding="utf-8") as handle:
+        data = json.load(handle)
+    if isinstance(data, dict) and isinstance(data.get("log"), dict):
+        records = []
+        for entry in data["log"].get("entries") or []:
+            request = entry.get("request") or {}
+            response = entry.get("response") or {}
+            url = request.get("url", "")
+            parts = urlsplit(url)
+            records.append(
+                {
+                    "method": request.get("method", "GET"),
+                    "host": parts.hostname or "",
+                    "path": parts.path or "/",
+                    "status_code": response.get("status", 0),
+                    "response_body": (response.get("content") or {}).get("text", ""),
+                }
+            )
+        return records
+    if isinstance(data, list) and all(isinstance(r, dict) for r in data):
+        return data
+    raise ValueError("expected a saved flow list or a HAR file")
+
+
+def _endpoint_key(record):
+    template, _ = _path_template(record.get("path"))
+    return f"{(record.get('method') or 'GET').upper()} {record.get('host') or ''}{template}"
+
+
+def _endpoint_summary(records):
+    endpoints = OrderedDict()
+    for r in records:
+        summary = endpoints.setdefault(_endpoint_key(r), {"statuses": set(), "shape": None})
+        summary["statuses"].add(int(r.get("status_code") or 0))
+        if summary["shape"] is None:
+            summary["shape"] = _body_schema(r.get("response_body"))
+    return endpoints
+
+
+def _diff_endpoints(baseline, current):
+    changed = []
+    for key in baseline.keys() & current.keys():
+        before, after = baseline[key], current[key]
+        details = []
+        if before["statuses"] != after["statuses"]:
+            details.append(f"status {sorted(before['statuses'])} -> {sorted(after['statuses'])}")
+        if before["shape"] and after["shape"] and before["shape"] != after["shape"]:
+            details.append("response shape changed")
+        if details:
+            changed.append({"endpoint": key, "detail": "; ".join(details)})
+    return {
+        "added": [key for key in current if key not in baseline],
+        "removed": [key for key in baseline if key not in current],
+        "changed": sorted(changed, key=lambda c: c["endpoint"]),
+    }
+
+
+def _diff_report(baseline_path, diff):
+    lines = [
+        f"PacketLens session diff against {baseline_path}",
+        f"Generated {datetime.now().strftime('%Y-%m-%d %H:%M')}",
+        "",
+        f"{len(diff['added'])} added, {len(diff['removed'])} removed, {len(diff['changed'])} changed endpoint(s)",
+        "",
+    ]
+    lines += [f"+ {endpoint}" for endpoint in diff["added"]]
+    lines += [f"- {endpoint}" for endpoint in diff["removed"]]
+    lines += [f"~ {change['endpoint']}: {change['detail']}" for change in diff["changed"]]
+    return "\n".join(lines) + "\n"
+
+
+def _redact_records(records, header_names, body_patterns):
+    """Redacts copies of the records; the originals are never modified."""
+    names = {n.strip().lower() for n in header_names if n and n.strip()}
+    patterns = [re.compile(p) for p in body_patterns if p]
+    redacted = copy.deepcopy(records)
+    count = 0
+    for record in redacted:
+        for key in ("request_headers", "response_headers"):
+            for header in record.get(key) or []:
+                if header["name"].lower() in names:
+                    header["value"] = REDACTED
+                    count += 1
+        for key in ("request_body", "response_body"):
+            for pattern in patterns:
+                record[key], hits = pattern.subn(REDACTED, record.get(key) or "")
+                count += hits
+    return redacted, count
+
+
+class FlowStore:
+    def __init__(self, max_flows=MAX_STORED_FLOWS):
+        self.max_flows = max_flows
+        self._flows = OrderedDict()
+        # Full (request, response) bodies for save-to-file and decoding, oldest dropped first past the byte cap.
+        self._bodies = OrderedDict()
+        self._body_bytes = 0
+        self._lock = threading.Lock()
+
+    def _drop_body(self, flow_id):
+        bodies = self._bodies.pop(flow_id, None)
+        if bodies is not None:
+            self._body_bytes -= sum(len(body) for body in bodies)
+
+    def add(self, record, request_body=b"", response_body=b""):
+        evicted = []
+        with self._lock:
+            if request_body or response_body:
+                self._drop_body(record["id"])
+                self._bodies[record["id"]] = (request_body, response_body)
+                self._body_bytes += len(request_body) + len(response_body)
+                while self._body_bytes > MAX_RETAINED_BODY_BYTES and len(self._bodies) > 1:
+                    self._drop_body(next(iter(self._bodies)))
+            previous = self._flows.get(record["id"])
+            record["pinned"] = bool(previous and previous.get("pinned"))
+            self._flows[record["id"]] = record
+            overflow = len(self._flows) - self.max_flows
+            if overflow > 0:
+                # Oldest unpinned flows go first; pinned flows are never evicted.
+                for flow_id, stored in self._flows.items():
+                    if len(evicted) >= overflow:
+                        break
+                    if not stored.get("pinned"):
+                        evicted.append(flow_id)
+                for flow_id in evicted:
+                    del self._flows[flow_id]
+                    self._drop_body(flow_id)
+        return evicted
+
+    def set_pinned(self, flow_id, pinned):
+        with self._lock:
+            record = self._flows.get(flow_id)
+            if record is None:
+                return False
+            record["pinned"] = pinned
+            return True
+
+    def set_client_outcome(self, flow_id, outcome):
+        with self._lock:
+            record = self._flows.get(flow_id)
+            if record is None:
+                return False
+            record["client_outcome"] = outcome
+            return True
+
+    def get(self, flow_id):
+        with self._lock:
+            return self._flows.get(flow_id)
+
+    def remove(self, flow_id):
+        with self._lock:
+            if self._flows.pop(flow_id, None) is None:
+                return False
+            self._drop_body(flow_id)
+            return True
+
+    def bodies(self, flow_id):
+        with self._lock:
+            return self._bodies.get(flow_id)
+
+    def snapshot(self):
+        with self._lock:
+            return list(self._flows.values())
+
+    def stats(self):
+        with self._lock:
+            total_body_bytes = sum(
+                (r.get("request_body_size") or 0) + (r.get("response_body_size") or 0)
+                for r in self._flows.values()
+            )
+            return len(self._flows), total_body_bytes
+
+    def remove_where(self, predicate):
+        with self._lock:
+            removed = [flow_id for flow_id, record in self._flows.items() if predicate(record)]
+            for flow_id in removed:
+                del self._flows[flow_id]
+                self._drop_body(flow_id)
+        return removed
+
+
+class FlowTail:
+    """Appends completed flows for one host pattern to a JSONL file."""
+
+    def __init__(self):
+        self.host_pattern = None
+        self.path = None
+        self._handle = None
+        self._lock = threading.Lock()
+
+    def start(self, host_pattern, path):
+        handle = open(path, "a", encoding="utf-8")
+        with self._lock:
+            if self._handle is not None:
+                self._handle.close()
+            self.host_pattern, self.path, self._handle = host_pattern, path, handle
+
+    def stop(self):
+        with self._lock:
+            if self._handle is not None:
+                self._handle.close()
+            self.host_pattern, self.path, self._handle = None, None, None
+
+    def write(self, record):
+        with self._lock:
+            if self._handle is None or not _matches_pattern(record.get("host"), self.host_pattern):
+                return
+            self._handle.write(json.dumps(record, separators=(",", ":")) + "\n")
+            self._handle.flush()
 
 
 class FlowCollector:
-    def __init__(self, out_queue, state):
+    def __init__(self, out_queue, state, store, tail):
         self.out_queue = out_queue
         self.state = state
+        self.store = store
+        self.tail = tail
+        # Location target URL -> (chain id, index the next hop will get).
+        self.redirect_targets = OrderedDict()
+        # Client connection id -> requests seen on it, for deriving HTTP/2 and HTTP/3 stream ids.
+        self.stream_counts = {}
+        # Upstream connections whose connect and TLS time has been attributed to a flow.
+        self.timed_servers = set()
 
-    def error(self, flow: http.HTTPFlow):
+    def requestheaders(self, flow: http.HTTPFlow):
+        version = flow.request.http_version.upper()
+        if not version.startswith(("HTTP/2", "HTTP/3")):
+            return
+        count = self.stream_counts.get(flow.client_conn.id, 0)
+        self.stream_counts[flow.client_conn.id] = count + 1
+        # Client-initiated streams are numbered in request order: 1, 3, 5... on h2 and 0, 4, 8... on h3.
+        flow.metadata["packetlens_stream_id"] = 4 * count if version.startswith("HTTP/3") else 2 * count + 1
+
+    def client_disconnected(self, client):
+        self.stream_counts.pop(client.id, None)
+
+    def server_disconnected(self, data):
+        self.timed_servers.discard(data.server.id)
+
+    def _protocol(self, flow):
+        """Returns (h1/h2/h3, stream id, pseudo-headers); the last two are None on HTTP/1."""
+        req = flow.request
+        version = req.http_version.upper()
+        if not version.startswith(("HTTP/2", "HTTP/3")):
+            return "h1", None, None
+        pseudo_headers = [
+            {"name": ":method", "value": req.method},
+            {"name": ":scheme", "value": req.scheme},
+            {"name": ":authority", "value": req.authority or req.host_header or req.host},
+            {"name": ":path", "value": req.path},
+        ]
+        if flow.response is not None:
+            pseudo_headers.append({"name": ":status", "value": str(flow.response.status_code)})
+        protocol = "h3" if version.startswith("HTTP/3") else "h2"
+        return protocol, flow.metadata.get("packetlens_stream_id"), pseudo_headers
+
+    def _redirect_fields(self, flow):
+        req = flow.request
+        resp = flow.response
+        chain = self.redirect_targets.pop(req.url, None)
+        location = _get_header_value(resp.headers, "location") if resp is not None else ""
+        if resp is not None and 300 <= resp.status_code < 400 and location:
+            chain_id, index = chain or (flow.id, 0)
+            self.redirect_targets[urljoin(req.url, location)] = (chain_id, index + 1)
+            while len(self.redirect_targets) > MAX_PENDING_REDIRECTS:
+                self.redirect_targets.popitem(last=False)
+            return chain_id, index
+        return chain or (None, None)
+
+    def _should_record(self, flow):
         if not self.state.should_capture():
+            return False
+        if not self.state.show_preflight and _is_preflight(flow.request):
+            return False
+        if not self.state.is_port_allowed(flow.request.port):
+            return False
+        if self.state.client_scope_token and flow.client_conn.id not in self.state.scoped_clients:
+            return False
+        if self.state.process_filter and flow.client_conn.id not in self.state.process_clients:
+            return False
+        return not self.state.is_muted(flow.request.host)
+
+    def _timings(self, flow):
+        req, resp, server = flow.request, flow.response, flow.server_conn
+        timings = {"dns_ms": flow.metadata.get("packetlens_dns_ms")}
+        # Connection setup is charged to the first flow that used the upstream connection.
+        if server and server.timestamp_start and server.id not in self.timed_servers:
+            self.timed_servers.add(server.id)
+            timings["connect_ms"] = _elapsed_ms(server.timestamp_start, server.timestamp_tcp_setup)
+            timings["tls_ms"] = _elapsed_ms(server.timestamp_tcp_setup, server.timestamp_tls_setup)
+        timings["send_ms"] = _elapsed_ms(req.timestamp_start, req.timestamp_end)
+        if resp:
+            # Waiting starts once the request is in and the upstream connection is ready.
+            ready = [req.timestamp_end]
+            if server:
+                ready += [server.timestamp_tcp_setup, server.timestamp_tls_setup]
+            timings["ttfb_ms"] = _elapsed_ms(max(t for t in ready if t), resp.timestamp_start)
+            timings["download_ms"] = _elapsed_ms(resp.timestamp_start, resp.timestamp_end)
+        return timings
+
+    def _fingerprint(self, flow):
+        return self.state.client_fingerprints.get(flow.client_conn.id, (None, None))
+
+    def _dedupe(self, record):
+        """Returns False when the record repeats a kept endpoint and should be dropped."""
+        keep = self.state.dedupe_keep
+        if not keep:
+            return True
+        key = _endpoint_key(record)
+        kept_id = self.state.endpoint_flows.get(key)
+        kept = self.store.get(kept_id) if kept_id else None
+        if kept is not None and keep == "first":
+            return False
+        if kept is not None and not kept.get("pinned") and self.store.remove(kept_id):
+            self.out_queue.put({"type": "flow_evicted", "flow_id": kept_id})
+        self.state.endpoint_flows[key] = record["id"]
+        return True
+
+    def _emit(self, record, request_body=b"", response_body=b""):
+        """Stores and publishes a record; request_body is the request as sent, so replays can resend it."""
+        if not self._dedupe(record):
+            return
+        # Bodies over the store limit are not retained at all, so saved bodies are always complete.
+        limit = self.state.store_body_limit
+        request_body = request_body if len(request_body) <= limit else b""
+        response_body = response_body if len(response_body) <= limit else b""
+        evicted = self.store.add(record, request_body, response_body)
+        self.tail.write(record)
+        self.out_queue.put({"type": "flow", "record": record})
+        for flow_id in evicted:
+            self.out_queue.put({"type": "flow_evicted", "flow_id": flow_id})
+
+    def error(self, flow: http.HTTPFlow):
+        if not self._should_record(flow):
             return
         req = flow.request
         started = req.timestamp_start or time.time()
@@ -172,6 +1539,18 @@ class FlowCollector:
         error_msg = ""
         if flow.error:
             error_msg = getattr(flow.error, "msg", str(flow.error))
+        if "packetlens_timeout" in flow.metadata:
+            error_msg = f"Request timed out after {flow.metadata['packetlens_timeout']}s"
+        if "packetlens_fault" in flow.metadata:
+            error_msg = flow.metadata["packetlens_fault"]
+        req_body = req.content or b""
+        capture_req = self.state.capture_request_body
+        req_text, req_truncated = _captured_body(
+            req_body, req.headers, capture_req, limit=self.state.stream_body_limit
+        )
+        chain_id, chain_index = self._redirect_fields(flow)
+        client_ja3, client_ja4 = self._fingerprint(flow)
+        protocol, stream_id, pseudo_headers = self._protocol(flow)
 
         record = {
             "id": flow.id,
@@ -181,24 +1560,55 @@ class FlowCollector:
             "method": req.method,
             "url": req.url,
             "host": req.host,
+            "host_header": req.host_header,
+            "user_agent": req.headers.get("user-agent"),
+            "original_user_agent": flow.metadata.get("packetlens_original_ua"),
             "path": req.path,
             "scheme": req.scheme,
+            "server_port": req.port,
+            "http_version": req.http_version,
+            "http_protocol": protocol,
+            "stream_id": stream_id,
+            "pseudo_headers": pseudo_headers,
             "status_code": 0,
             "request_headers": _headers_to_list(req.headers),
             "response_headers": None,
-            "request_body_size": len(req.content or b""),
+            "response_trailers": None,
+            "request_body_size": len(req_body),
             "response_body_size": 0,
-            "request_body": _decode_for_display(_truncate_bytes(req.content or b""), req.headers),
+            "response_body_decoded_size": None,
+            "request_body": req_text,
             "response_body": "",
-            "request_body_truncated": len(req.content or b"") > MAX_BODY_CAPTURE,
+            "request_body_truncated": req_truncated,
+            "multipart_parts": _parse_multipart(req_body, req.headers) if capture_req else None,
+            "request_body_hash": _body_hash(req_body),
+            "response_body_hash": None,
             "response_body_truncated": False,
             "error": error_msg,
+            "redirect_chain_id": chain_id,
+            "redirect_index": chain_index,
+            "timings": self._timings(flow),
+            "tls_handshake": _tls_details(flow.server_conn),
+            "certificate_chain": _certificate_chain(flow.server_conn),
+            "client_conn_id": flow.client_conn.id,
+            "client_ip": flow.client_conn.peername[0] if flow.client_conn.peername else None,
+            "client_port": flow.client_conn.peername[1] if flow.client_conn.peername else None,
+            "server_conn_id": flow.server_conn.id if flow.server_conn and flow.server_conn.timestamp_start else None,
+            "client_ja3": client_ja3,
+            "client_ja4": client_ja4,
+            "client_hello_b64": self.state.client_hellos.get(flow.client_conn.id),
+            "replay_target": flow.metadata.get("packetlens_replay_target"),
+            "replay_of": flow.metadata.get("packetlens_replay_of"),
+            "replay_edits": self.state.replay_edits.pop(flow.metadata.get("packetlens_replay_edit"), None),
+            "mapped_from": flow.metadata.get("packetlens_mapped_from"),
+            "mocked": bool(flow.metadata.get("packetlens_mocked")),
+            "streamed": bool(flow.metadata.get("packetlens_streamed")),
             "started_iso": _iso_time(started),
         }
-        self.out_queue.put({"type": "flow", "record": record})
+        self._emit(record, req.raw_content or b"")
 
     def response(self, flow: http.HTTPFlow):
-        if not self.state.should_capture():
+        if not self._should_record(flow):
             return
         req = flow.request
         resp = flow.response
@@ -206,8 +1616,27 @@ class FlowCollector:
         started = req.timestamp_start or time.time()
         ended = resp.timestamp_end or time.time()
         duration_ms = max(0, int((ended - started) * 1000))
-        resp_body = resp.content or b""
+        # raw_content is what crossed the wire; content has any Content-Encoding undone.
+        encoding = resp.headers.get("content-encoding", "").lower()
+        keep_raw = "br" in encoding and not self.state.brotli_decode
+        resp_body = (resp.raw_content if keep_raw else resp.content) or b""
+        wire_size = len(resp.raw_content or b"")
+        decoded_size = len(resp_body) if encoding and not keep_raw else None
         req_body = req.content or b""
+        capture_req = self.state.capture_request_body
+        req_text, req_truncated = _captured_body(
+            req_body, req.headers, capture_req, limit=self.state.stream_body_limit
+        )
+        resp_text, resp_truncated = _captured_body(
+            resp_body,
+            resp.headers if resp else None,
+            self.state.capture_response_body,
+            not keep_raw,
+            self.state.stream_body_limit,
+        )
+        chain_id, chain_index = self._redirect_fields(flow)
+        client_ja3, client_ja4 = self._fingerprint(flow)
+        protocol, stream_id, pseudo_headers = self._protocol(flow)
 
         record = {
             "id": flow.id,
@@ -217,72 +1646,789 @@ class FlowCollector:
             "method": req.method,
             "url": req.url,
             "host": req.host,
+            "host_header": req.host_header,
+            "user_agent": req.headers.get("user-agent"),
+            "original_user_agent": flow.metadata.get("packetlens_original_ua"),
             "path": req.path,
             "scheme": req.scheme,
+            "server_port": req.port,
+            "http_version": resp.http_version if resp else req.http_version,
+            "http_protocol": protocol,
+            "stream_id": stream_id,
+            "pseudo_headers": pseudo_headers,
             "status_code": resp.status_code if resp else 0,
             "request_headers": _headers_to_list(req.headers),
             "response_headers": _headers_to_list(resp.headers) if resp else None,
+            "response_trailers": _headers_to_list(resp.trailers) if resp and resp.trailers else None,
             "request_body_size": len(req_body),
-            "response_body_size": len(resp_body),
-            "request_body": _decode_for_display(_truncate_bytes(req_body), req.headers),
-            "response_body": _decode_for_display(_truncate_bytes(resp_body), resp.headers if resp else None),
-            "request_body_truncated": len(req_body) > MAX_BODY_CAPTURE,
-            "response_body_truncated": len(resp_body) > MAX_BODY_CAPTURE,
-            "error": "",
+            "response_body_size": wire_size,
+            "response_body_decoded_size": decoded_size,
+            "content_length_mismatch": _content_length_mismatch(req, resp),
+            "request_body": req_text,
+            "response_body": resp_text,
+            "request_body_truncated": req_truncated,
+            "multipart_parts": _parse_multipart(req_body, req.headers) if capture_req else None,
+            "request_body_hash": _body_hash(req_body),
+            "response_body_hash": _body_hash(resp_body),
+            "response_body_truncated": resp_truncated,
+            "error": flow.metadata.get("packetlens_fault", ""),
+            "redirect_chain_id": chain_id,
+            "redirect_index": chain_index,
+            "timings": self._timings(flow),
+            "tls_handshake": _tls_details(flow.server_conn),
+            "certificate_chain": _certificate_chain(flow.server_conn),
+            "client_conn_id": flow.client_conn.id,
+            "client_ip": flow.client_conn.peername[0] if flow.client_conn.peername else None,
+            "client_port": flow.client_conn.peername[1] if flow.client_conn.peername else None,
+            "server_conn_id": flow.server_conn.id if flow.server_conn and flow.server_conn.timestamp_start else None,
+            "client_ja3": client_ja3,
+            "client_ja4": client_ja4,
+            "client_hello_b64": self.state.client_hellos.get(flow.client_conn.id),
+            "replay_target": flow.metadata.get("packetlens_replay_target"),
+            "replay_of": flow.metadata.get("packetlens_replay_of"),
+            "replay_edits": self.state.replay_edits.pop(flow.metadata.get("packetlens_replay_edit"), None),
+            "mapped_from": flow.metadata.get("packetlens_mapped_from"),
+            "mocked": bool(flow.metadata.get("packetlens_mocked")),
+            "streamed": bool(flow.metadata.get("packetlens_streamed")),
             "started_iso": _iso_time(started),
         }
-        self.out_queue.put({"type": "flow", "record": record})
+        if _get_header_value(req.headers, "content-type").lower().startswith("application/grpc"):
+            record["grpc"] = _decode_grpc_flow(self.state.proto_pool, record, req_body, resp_body)
+        self._emit(record, req.raw_content or b"", resp_body)
 
 
-class ProxyService:
-    def __init__(self, event_queue):
-        self.event_queue = event_queue
-        self.state = CaptureState()
-        self.proxy_thread = None
-        self.proxy_master = None
-        self.proxy_loop = None
-        self.current_port = None
-        self._lock = threading.Lock()
-        self._start_in_progress = False
-        self._last_start_error = ""
+class UpstreamTweaks:
+    """Adjusts upstream connections and what gets forwarded on the request path."""
+
+    def __init__(self, state):
+        self.state = state
+
+    def server_connect(self, data):
+        server = data.server
+        host = (server.address or ("",))[0]
+        for pattern, sni in list(self.state.sni_overrides.items()):
+            if _matches_pattern(host, pattern):
+                server.sni = sni
+                break
+        peername = data.client.peername
+        pinned = self.state.handshake_pins.get(tuple(peername[:2])) if peername else None
+        if pinned:
+            server.sni = pinned.get("sni") or server.sni
+            if pinned.get("alpn"):
+                server.alpn_offers = [pinned["alpn"].encode("latin-1")]
+        elif self.state.forces_http1(host):
+            server.alpn_offers = [b"http/1.1"]
+
+    def requestheaders(self, flow: http.HTTPFlow):
+        target = flow.request.headers.pop(REPLAY_TARGET_HEADER, None)
+        if target:
+            flow.metadata["packetlens_replay_target"] = target
+        replay_of = flow.request.headers.pop(REPLAY_OF_HEADER, None)
+        if replay_of:
+            flow.metadata["packetlens_replay_of"] = replay_of
+        replay_edit = flow.request.headers.pop(REPLAY_EDIT_HEADER, None)
+        if replay_edit:
+            flow.metadata["packetlens_replay_edit"] = replay_edit
+        if self.state.strip_accept_encoding:
+            flow.request.headers.pop("accept-encoding", None)
+        if self.state.user_agent is not None:
+            flow.metadata["packetlens_original_ua"] = flow.request.headers.get("user-agent")
+            flow.request.headers["user-agent"] = self.state.user_agent
+        # HTTP/1 connections marked close are dropped after the response instead of pooled.
+        # HTTP/2 forbids the header, so multiplexed connections keep being reused.
+        if not self.state.connection_reuse and flow.request.http_version.startswith("HTTP/1"):
+            flow.request.headers["connection"] = "close"
+
+
+class PortScope:
+    """Passes connections to ports outside the allowed list through untouched."""
+
+    def __init__(self, state):
+        self.state = state
+
+    def next_layer(self, nextlayer):
+        address = nextlayer.context.server.address
+        if address and not self.state.is_port_allowed(address[1]):
+            nextlayer.layer = layers.TCPLayer(nextlayer.context, ignore=True)
+
+
+def _apply_request_changes(req, changes):
+    if changes.get("headers") is not None:
+        req.headers = http.Headers(
+            [(name.encode("utf-8"), value.encode("utf-8")) for name, value in _header_items(changes["headers"])]
+        )
+    if changes.get("method"):
+        req.method = changes["method"].upper()
+    # The URL goes after the headers so Host follows an edited URL.
+    if changes.get("url"):
+        req.url = changes["url"]
+    if changes.get("body") is not None:
+        req.content = changes["body"].encode("utf-8")
+
+
+class Breakpoints:
+    """Holds requests matching a breakpoint until the UI resumes, modifies or drops them."""
+
+    def __init__(self, state, out_queue):
+        self.state = state
+        self.out_queue = out_queue
+
+    async def request(self, flow: http.HTTPFlow):
+        if flow.response is not None or not self.state.breakpoint_hit(flow.request):
+            return
+        req = flow.request
+        loop = asyncio.get_running_loop()
+        decision = loop.create_future()
+        self.state.intercepted[flow.id] = (loop, decision)
+        self.out_queue.put(
+            {
+                "type": "intercepted",
+                "flow_id": flow.id,
+                "method": req.method,
+                "url": req.url,
+                "request_headers": _headers_to_list(req.headers),
+                "request_body": _captured_body(req.content or b"", req.headers, True)[0],
+            }
+        )
+        try:
+            action, changes = await decision
+        finally:
+            self.state.intercepted.pop(flow.id, None)
+        if action == "drop":
+            flow.kill()
+        elif action == "modify":
+            _apply_request_changes(req, changes)
+
+
+class FaultInjector:
+    """Fails requests matching a fault rule with a reset, a timeout or a 5xx response."""
+
+    def __init__(self, state):
+        self.state = state
+
+    async def request(self, flow: http.HTTPFlow):
+        if flow.response is not None:
+            return
+        fault = self.state.fault_for(flow.request.url)
+        if fault is None:
+            return
+        if fault in ("http_500", "http_503"):
+            status = int(fault[-3:])
+            flow.metadata["packetlens_fault"] = f"Fault injected: HTTP {status}"
+            flow.response = http.Response.make(status, b"", {"x-packetlens": "fault"})
+            return
+        if fault == "timeout":
+            flow.metadata["packetlens_fault"] = f"Fault injected: timed out after {FAULT_TIMEOUT_SECONDS}s"
+            await asyncio.sleep(FAULT_TIMEOUT_SECONDS)
+        else:
+            flow.metadata["packetlens_fault"] = "Fault injected: connection reset"
+        if flow.killable:
+            flow.kill()
+
+
+class MockResponder:
+    """Answers requests matching a mock rule with a stubbed response, after the rule's latency."""
+
+    def __init__(self, state):
+        self.state = state
+
+    async def request(self, flow: http.HTTPFlow):
+        if flow.response is not None:
+            return
+        rule = self.state.mock_for(flow.request)
+        if rule is None:
+            return
+        if rule["latency_ms"]:
+            await asyncio.sleep(rule["latency_ms"] / 1000)
+        headers = http.Headers(
+            [(name.encode("utf-8"), value.encode("utf-8")) for name, value in _header_items(rule["headers"])]
+        )
+        headers["x-packetlens"] = "mock"
+        flow.response = _local_response(
+            rule["status"],
+            rule["body"].encode("utf-8"),
+            headers,
+            rule["compress"],
+            flow.request.headers.get("accept-encoding", ""),
+        )
+        flow.metadata["packetlens_mocked"] = True
+
+
+class MapLocal:
+    """Answers requests matching a map-local rule with a file from disk."""
+
+    def __init__(self, state):
+        self.state = state
+
+    def request(self, flow: http.HTTPFlow):
+        if flow.response is not None:
+            return
+        rule = self.state.map_local_for(flow.request.url)
+        if rule is None:
+            return
+        try:
+            path = _map_local_file(rule["local_path"], flow.request.path)
+            with open(path, "rb") as handle:
+                body = handle.read()
+        except (OSError, ValueError) as exc:
+            flow.response = http.Response.make(
+                404,
+                f"Map local failed: {exc}".encode("utf-8"),
+                {"content-type": "text/plain", "x-packetlens": "map-local"},
+            )
+            return
+        content_type = rule["content_type"] or mimetypes.guess_type(path)[0] or "application/octet-stream"
+        flow.response = _local_response(
+            200,
+            body,
+            {"content-type": content_type, "x-packetlens": "map-local"},
+            rule["compress"],
+            flow.request.headers.get("accept-encoding", ""),
+        )
+
+
+class MapRemote:
+    """Sends requests matching a map-remote rule to another host, port or base path."""
+
+    def __init__(self, state):
+        self.state = state
+
+    def request(self, flow: http.HTTPFlow):
+        if flow.response is not None:
+            return
+        rule = self.state.map_remote_for(flow.request.url)
+        if rule is None:
+            return
+        req = flow.request
+        original_url, original_host = req.url, req.host_header
+        req.url = _retarget_url(original_url, rule["target_url"])
+        if rule["preserve_host"] and original_host:
+            req.host_header = original_host
+        flow.metadata["packetlens_mapped_from"] = original_url
+
+
+class RecordOnlyResponder:
+    """Answers requests with a canned response instead of forwarding them upstream."""
+
+    def __init__(self, state):
+        self.state = state
+
+    def request(self, flow: http.HTTPFlow):
+        status = self.state.record_only_status
+        if status and flow.response is None:
+            flow.response = http.Response.make(
+                status, b"", {"content-type": "text/plain", "x-packetlens": "record-only"}
+            )
+
+
+class ClientScope:
+    """Marks client connections from the scoped browser profile or from processes the process filter allows.
+
+    Both checks share one socket-owner lookup per connection.
+    """
+
+    def __init__(self, state, out_queue):
+        self.state = state
+        self.out_queue = out_queue
+
+    async def client_connected(self, client):
+        token = self.state.client_scope_token
+        allowed = self.state.process_filter
+        if not (token or allowed) or not client.peername:
+            return
+        try:
+            owner = await asyncio.get_running_loop().run_in_executor(None, _client_process, client.peername)
+        except Exception as exc:
+            self.out_queue.put({"type": "error", "message": f"Cannot attribute client connection: {exc}"})
+            return
+        if owner is None:
+            return
+        pid, name, cmdline = owner
+        if token and any(token in arg for arg in cmdline):
+            self.state.scoped_clients.add(client.id)
+        if allowed:
+            pids, names = allowed
+            if pid in pids or name.lower().removesuffix(".exe") in names:
+                self.state.process_clients.add(client.id)
+
+    def client_disconnected(self, client):
+        self.state.scoped_clients.discard(client.id)
+        self.state.process_clients.discard(client.id)
+
+
+class TlsFingerprinter:
+    """Records JA3/JA4 fingerprints and raw ClientHellos of connecting clients when enabled."""
+
+    def __init__(self, state):
+        self.state = state
+
+    def tls_clienthello(self, data):
+        client_id = data.context.client.id
+        if self.state.capture_client_hello:
+            try:
+                raw = data.client_hello.raw_bytes(wrap_in_record=True)
+            except Exception:
+                raw = None
+            if raw:
+                self.state.client_hellos[client_id] = base64.b64encode(raw).decode("ascii")
+        if not self.state.capture_tls_fingerprints:
+            return
+        try:
+            fingerprints = _tls_fingerprints(data.client_hello)
+        except Exception:
+            return
+        self.state.client_fingerprints[client_id] = fingerprints
+
+    def client_disconnected(self, client):
+        self.state.client_fingerprints.pop(client.id, None)
+        self.state.client_hellos.pop(client.id, None)
+
+
+class CustomDnsResolver:
+    """Resolves upstream hosts through the configured DNS servers instead of the system resolver."""
+
+    def __init__(self, state, out_queue):
+        self.state = state
+        self.out_queue = out_queue
+
+    async def server_connect(self, data):
+        servers = list(self.state.dns_servers)
+        server = data.server
+        if not servers or not server.address:
+            return
+        host, port = server.address
+        try:
+            ipaddress.ip_address(host)
+            return
+        except ValueError:
+            pass
+        loop = asyncio.get_running_loop()
+        errors = []
+        for dns_server in servers:
+            try:
+                address = await loop.run_in_executor(None, _dns_lookup, dns_server, host)
+            except (OSError, IndexError) as exc:
+                errors.append(f"{dns_server[0]}: {exc}")
+                continue
+            server.sni = server.sni or host
+            server.address = (address, port)
+            return
+        self.out_queue.put(
+            {
+                "type": "error",
+                "message": f"DNS lookup for {host} failed ({'; '.join(errors)}); using the system resolver",
+            }
+        )
+
+
+class HostThrottle:
+    """Delays requests and paces bodies for throttled hosts and the network-wide throttle."""
+
+    def __init__(self, state):
+        self.state = state
+
+    async def request(self, flow: http.HTTPFlow):
+        _, latency_ms = self.state.throttle_for(flow.request.host)
+        _, up_kbps, network_latency_ms = self.state.network_throttle
+        latency_ms = latency_ms or network_latency_ms
+        if latency_ms:
+            await asyncio.sleep(latency_ms / 1000)
+        size = len(flow.request.raw_content or b"")
+        if up_kbps and size:
+            await asyncio.sleep(size * 8 / (up_kbps * 1000))
+
+    async def response(self, flow: http.HTTPFlow):
+        down_kbps, _ = self.state.throttle_for(flow.request.host)
+        down_kbps = down_kbps or self.state.network_throttle[0]
+        size = len(flow.response.raw_content or b"") if flow.response else 0
+        if down_kbps and size:
+            await asyncio.sleep(size * 8 / (down_kbps * 1000))
+
+
+class StatusLatency:
+    """Holds back responses whose upstream status has a latency rule."""
+
+    def __init__(self, state):
+        self.state = state
+
+    async def response(self, flow: http.HTTPFlow):
+        latency_ms = self.state.status_latency.get(flow.response.status_code) if flow.response else None
+        if latency_ms:
+            await asyncio.sleep(latency_ms / 1000)
+
+
+class RequestTimeouts:
+    """Kills upstream requests that outlive the configured global timeout."""
+
+    def __init__(self, state):
+        self.state = state
+        self.pending = {}
+        self._task = None
+
+    def running(self):
+        self._task = asyncio.get_running_loop().create_task(self._watch())
+
+    def done(self):
+        if self._task is not None:
+            self._task.cancel()
+
+    def requestheaders(self, flow: http.HTTPFlow):
+        self.pending[flow.id] = (flow, time.monotonic())
+
+    def response(self, flow: http.HTTPFlow):
+        self.pending.pop(flow.id, None)
+
+    def error(self, flow: http.HTTPFlow):
+        self.pending.pop(flow.id, None)
+
+    async def _watch(self):
+        while True:
+            await asyncio.sleep(0.5)
+            limit = self.state.request_timeout
+            if not limit:
+                continue
+            now = time.monotonic()
+            for flow_id, (flow, started) in list(self.pending.items()):
+                if now - started < limit:
+                    continue
+                self.pending.pop(flow_id, None)
+                flow.metadata["packetlens_timeout"] = limit
+                if flow.killable:
+                    flow.kill()
+
+
+class DnsTimer:
+    """Times an explicit DNS lookup per request when resolve timings are enabled."""
+
+    def __init__(self, state):
+        self.state = state
+
+    async def requestheaders(self, flow: http.HTTPFlow):
+        if not self.state.resolve_timings:
+            return
+        req = flow.request
+        started = time.perf_counter()
+        try:
+            await asyncio.get_running_loop().getaddrinfo(req.host, req.port, type=socket.SOCK_STREAM)
+        except OSError:
+            return
+        flow.metadata["packetlens_dns_ms"] = round((time.perf_counter() - started) * 1000, 2)
+
+
+class BypassDetector:
+    """Emits hints when the client appears to route traffic around the proxy."""
+
+    def __init__(self, out_queue):
+        self.out_queue = out_queue
+        self.client_seen = threading.Event()
+        self.hinted_hosts = set()
+
+    def _hint(self, reason, host=None):
+        self.out_queue.put({"type": "direct_connection_hint", "reason": reason, "host": host})
+
+    def client_connected(self, client):
+        self.client_seen.set()
+
+    def http_connect(self, flow: http.HTTPFlow):
+        host = (flow.request.host or "").lower()
+        if host in DOH_HOSTS and host not in self.hinted_hosts:
+            self.hinted_hosts.add(host)
+            self._hint(
+                "Client is using DNS-over-HTTPS; disable secure DNS if some sites never appear.",
+                host,
+            )
+
+    def check_idle(self):
+        if not self.client_seen.is_set():
+            self._hint(
+                f"No client has connected in {IDLE_HINT_SECONDS}s. Check that the browser or app uses this proxy."
+            )
+
+
+class SseStreamer:
+    """Relays text/event-stream responses as they arrive instead of buffering them until close."""
+
+    def __init__(self, state, out_queue):
+        self.state = state
+        self.out_queue = out_queue
+
+    def responseheaders(self, flow: http.HTTPFlow):
+        resp = flow.response
+        if "text/event-stream" not in _get_header_value(resp.headers, "content-type").lower():
+            return
+        if not self.state.should_capture() or self.state.is_muted(flow.request.host):
+            resp.stream = True
+            return
+        flow.metadata["packetlens_streamed"] = True
+        encoding = _get_header_value(resp.headers, "content-encoding").lower()
+        if "gzip" in encoding:
+            decoder = zlib.decompressobj(16 + zlib.MAX_WBITS)
+        elif "deflate" in encoding:
+            decoder = zlib.decompressobj()
+        else:
+            decoder = None
+        sequence = itertools.count()
+
+        def relay(chunk):
+            data = chunk
+            if decoder is not None:
+                try:
+                    data = decoder.decompress(chunk)
+                except zlib.error:
+                    data = b""
+            if data:
+                self.out_queue.put(
+                    {
+                        "type": "stream_chunk",
+                        "flow_id": flow.id,
+                        "sequence": next(sequence),
+                        "data": data.decode("utf-8", "replace"),
+                        "timestamp": time.time(),
+                    }
+                )
+            return chunk
+
+        resp.stream = relay
+
+
+class WebSocketTap:
+    """Streams WebSocket frames as they pass and a summary of each connection when it closes."""
+
+    def __init__(self, state, out_queue):
+        self.state = state
+        self.out_queue = out_queue
+
+    def _wanted(self, flow):
+        return self.state.should_capture() and not self.state.is_muted(flow.request.host)
+
+    def websocket_message(self, flow: http.HTTPFlow):
+        if not self._wanted(flow):
+            return
+        message = flow.websocket.messages[-1]
+        payload = message.content[:MAX_BODY_CAPTURE]
+        self.out_queue.put(
+            {
+                "type": "websocket_message",
+                "flow_id": flow.id,
+                "direction": "client" if message.from_client else "server",
+                "opcode": int(message.type),
+                "payload": (
+                    payload.decode("utf-8", "replace")
+                    if message.is_text
+                    else base64.b64encode(payload).decode("ascii")
+                ),
+                "timestamp": message.timestamp,
+                "truncated": len(message.content) > MAX_BODY_CAPTURE,
+            }
+        )
+
+    def websocket_end(self, flow: http.HTTPFlow):
+        if not self._wanted(flow):
+            return
+        ws = flow.websocket
+        sent = [m for m in ws.messages if m.from_client]
+        received = [m for m in ws.messages if not m.from_client]
+        started = flow.response.timestamp_end if flow.response else flow.request.timestamp_start
+        self.out_queue.put(
+            {
+                "type": "websocket_closed",
+                "summary": {
+                    "flow_id": flow.id,
+                    "url": flow.request.url,
+                    "host": flow.request.host,
+                    "started": started or time.time(),
+                    "ended": ws.timestamp_end or time.time(),
+                    "messages_sent": len(sent),
+                    "messages_received": len(received),
+                    "bytes_sent": sum(len(m.content) for m in sent),
+                    "bytes_received": sum(len(m.content) for m in received),
+                    "close_code": ws.close_code,
+                    "close_reason": ws.close_reason or None,
+                },
+            }
+        )
+
+
+class HandshakeWatcher:
+    """Reports upstream TLS failures for hosts whose handshake is recorded, replayed or forced to HTTP/1.1."""
+
+    def __init__(self, state, out_queue):
+        self.state = state
+        self.out_queue = out_queue
+
+    def tls_failed_server(self, data):
+        server = data.context.server
+        host = (server.address or ("",))[0]
+        watched = any(_matches_pattern(host, p) for p in list(self.state.handshake_hosts))
+        client = data.context.client.peername
+        pinned = client is not None and tuple(client[:2]) in self.state.handshake_pins
+        if watched or pinned or self.state.forces_http1(host):
+            error = getattr(data.conn, "error", None) or "unknown error"
+            self.out_queue.put({"type": "error", "message": f"TLS handshake with {host} failed: {error}"})
+
+
+class ErrorTrip:
+    """Pauses capture on the first failing flow when pause-on-error is enabled."""
+
+    def __init__(self, service):
+        self.service = service
+
+    def response(self, flow: http.HTTPFlow):
+        status_min = self.service.state.pause_status_min
+        if status_min and flow.response is not None and flow.response.status_code >= status_min:
+            self._trip(flow, f"HTTP {flow.response.status_code}")
+
+    def error(self, flow: http.HTTPFlow):
+        self._trip(flow, "error")
+
+    def _trip(self, flow, reason):
+        state = self.service.state
+        if state.pause_on_error and not state.paused.is_set() and state.should_capture():
+            self.service.trip_pause(f"Paused on {reason} in flow {flow.id}")
+
+
+class HealthMonitor:
+    """Reports partial failures that leave core capture working."""
+
+    def __init__(self, service):
+        self.service = service
+        self.failed_tls_hosts = set()
+
+    def tls_failed_server(self, data):
+        server = getattr(getattr(data, "context", None), "server", None)
+        host = getattr(server, "sni", None) or (getattr(server, "address", None) or ("unknown",))[0]
+        if host in self.failed_tls_hosts:
+            return
+        self.failed_tls_hosts.add(host)
+        hosts = ", ".join(sorted(self.failed_tls_hosts)[:5])
+        self.service.mark_degraded(
+            "tls", f"Upstream TLS failed for {len(self.failed_tls_hosts)} host(s): {hosts}"
+        )
+
+
+class ProxyService:
+    def __init__(self, event_queue):
+        self.event_queue = event_queue
+        self.state = CaptureState()
+        self.flow_store = FlowStore()
+        self.flow_tail = FlowTail()
+        self.client = ProxyClient(self)
+        self.bypass_detector = BypassDetector(event_queue)
+        self.proxy_thread = None
+        self.proxy_master = None
+        self.proxy_loop = None
+        self.current_port = None
+        self.current_host = LOOPBACK_HOST
+        # Whether mitmproxy's HTTP/3 support was enabled for the current run.
+        self.http3 = False
+        # Process names or PIDs redirected into the proxy by mitmproxy's WinDivert-based local mode.
+        self.transparent_processes = []
+        self.ipc_port = None
+        self._lock = threading.Lock()
+        self._start_in_progress = False
+        self._last_start_error = ""
+        self._degraded = {}
+        self.last_error = None
+        self.markers = []
+        self.capture_started_at = None
 
     def _running_message(self, port):
-        return f"Proxy Running on 127.0.0.1:{port}" if port else "Proxy Running"
+        return f"Proxy Running on {self.current_host}:{port}" if port else "Proxy Running"
+
+    def _passthrough_message(self, port):
+        if port:
+            return f"Passthrough on {self.current_host}:{port} (not capturing)"
+        return "Passthrough (not capturing)"
+
+    def _status_payload(self, status, message, port=None, detail=None):
+        return {
+            "type": "status",
+            "status": status,
+            "message": message,
+            "port": port,
+            "bind_address": self.current_host if port else None,
+            "detail": detail,
+            "proxy_port": port,
+            "ipc_port": self.ipc_port,
+            "http3": self.http3,
+            "transparent_processes": self.transparent_processes,
+        }
 
     def current_status_payload(self):
         port = self.current_port
         if self.proxy_thread and self.proxy_thread.is_alive():
+            if self.state.passthrough.is_set():
+                return self._status_payload("passthrough", self._passthrough_message(port), port)
             if self.state.paused.is_set():
-                return {"type": "status", "status": "paused", "message": "Paused", "port": port}
+                return self._status_payload("paused", "Paused", port)
+            if self.state.capture_enabled.is_set() and self.state.record_only_status:
+                return self._status_payload(
+                    "record_only",
+                    f"Record-only on {self.current_host}:{port} (answering {self.state.record_only_status})",
+                    port,
+                )
+            if self.state.capture_enabled.is_set() and self._degraded:
+                return self._status_payload(
+                    "degraded",
+                    f"{self._running_message(port)} (degraded)",
+                    port,
+                    "; ".join(self._degraded.values()),
+                )
             if self.state.capture_enabled.is_set():
-                return {
-                    "type": "status",
-                    "status": "running",
-                    "message": self._running_message(port),
-                    "port": port,
-                }
-        return {"type": "status", "status": "stopped", "message": "Ready", "port": port}
+                return self._status_payload("running", self._running_message(port), port)
+        return self._status_payload("stopped", "Ready", port)
 
-    def _candidate_ports(self, requested_port):
+    def _candidate_ports(self, requested_port, auto_port):
         yield requested_port
+        if not auto_port:
+            return
         for candidate in range(requested_port + 1, requested_port + 21):
             yield candidate
+        # Nothing free near the requested port; let the OS hand out one.
+        try:
+            yield _ephemeral_port(self.current_host)
+        except OSError:
+            return
 
-    def start(self, port):
+    def start(
+        self, port, bind_scope="loopback", auto_port=False, http3=False, transparent_processes=(), confirm_lan=False
+    ):
+        listen_host = _bind_host(bind_scope)
+        if listen_host == ALL_INTERFACES_HOST and not confirm_lan:
+            self.event_queue.put({"type": "error", "message": "LAN mode needs explicit confirmation"})
+            return
+        transparent_processes = [p.strip() for p in transparent_processes if p.strip()]
         with self._lock:
             previous_port = self.current_port
             if self.proxy_thread and self.proxy_thread.is_alive():
-                # If already running on requested port, just resume capture.
-                if previous_port == port:
+                # If already running with the same listener and modes, just resume capture.
+                if (
+                    previous_port == port
+                    and self.current_host == listen_host
+                    and self.http3 == http3
+                    and self.transparent_processes == transparent_processes
+                ):
                     self.state.capture_enabled.set()
                     self.state.paused.clear()
-                    self._status("running", self._running_message(port), port=port)
+                    self.state.passthrough.clear()
+                    self.event_queue.put(self.current_status_payload())
                     return
-                # Restart on a different port.
+                # Restart on a different port or interface.
                 self._shutdown_proxy_locked()
 
+            self.current_host = listen_host
+            self.http3 = http3
+            self.transparent_processes = transparent_processes
+            modes = ["regular"]
+            if transparent_processes:
+                # Local mode asks for elevation once and redirects all of these processes' traffic.
+                modes.append("local:" + ",".join(transparent_processes))
+            self._degraded.pop("tls", None)
+            self.bypass_detector = BypassDetector(self.event_queue)
+
             self.state.capture_enabled.set()
             self.state.paused.clear()
+            self.state.passthrough.clear()
             self._start_in_progress = True
             self._last_start_error = ""
 
@@ -290,10 +2436,38 @@ class ProxyService:
                 loop = asyncio.new_event_loop()
                 asyncio.set_event_loop(loop)
                 self.proxy_loop = loop
-                opts = options.Options(listen_host="127.0.0.1", listen_port=listen_port, ssl_insecure=True)
+                opts = options.Options(
+                    listen_host=listen_host,
+                    listen_port=listen_port,
+                    ssl_insecure=True,
+                    connection_strategy=self._connection_strategy(),
+                    http3=http3,
+                    mode=modes,
+                )
                 master = DumpMaster(opts, loop=loop, with_termlog=False, with_dumper=False)
                 self.proxy_master = master
-                master.addons.add(FlowCollector(self.event_queue, self.state))
+                master.addons.add(FlowCollector(self.event_queue, self.state, self.flow_store, self.flow_tail))
+                master.addons.add(UpstreamTweaks(self.state))
+                master.addons.add(CustomDnsResolver(self.state, self.event_queue))
+                master.addons.add(TlsFingerprinter(self.state))
+                master.addons.add(ClientScope(self.state, self.event_queue))
+                master.addons.add(PortScope(self.state))
+                master.addons.add(Breakpoints(self.state, self.event_queue))
+                master.addons.add(FaultInjector(self.state))
+                master.addons.add(MockResponder(self.state))
+                master.addons.add(MapLocal(self.state))
+                master.addons.add(MapRemote(self.state))
+                master.addons.add(RecordOnlyResponder(self.state))
+                master.addons.add(HostThrottle(self.state))
+                master.addons.add(StatusLatency(self.state))
+                master.addons.add(RequestTimeouts(self.state))
+                master.addons.add(DnsTimer(self.state))
+                master.addons.add(self.bypass_detector)
+                master.addons.add(HandshakeWatcher(self.state, self.event_queue))
+                master.addons.add(WebSocketTap(self.state, self.event_queue))
+                master.addons.add(SseStreamer(self.state, self.event_queue))
+                master.addons.add(ErrorTrip(self))
+                master.addons.add(HealthMonitor(self))
                 try:
                     result = master.run()
                     if asyncio.iscoroutine(result):
@@ -317,15 +2491,21 @@ class ProxyService:
                     self.proxy_thread = None
 
             started = False
-            for candidate in self._candidate_ports(port):
+            requested_port_busy = False
+            for candidate in self._candidate_ports(port, auto_port):
                 # Fast skip for ports that are already occupied.
-                if _port_in_use("127.0.0.1", candidate):
+                if _port_in_use(LOOPBACK_HOST, candidate):
+                    requested_port_busy = requested_port_busy or candidate == port
                     continue
                 self.current_port = candidate
                 self.proxy_thread = threading.Thread(target=run_proxy, args=(candidate,), daemon=True)
                 self.proxy_thread.start()
-                if _wait_for_port("127.0.0.1", candidate, timeout=2.5) and self.proxy_thread.is_alive():
-                    self._status("running", self._running_message(candidate), port=candidate)
+                if _wait_for_port(LOOPBACK_HOST, candidate, timeout=2.5) and self.proxy_thread.is_alive():
+                    self.capture_started_at = time.time()
+                    self.event_queue.put(self.current_status_payload())
+                    idle_check = threading.Timer(IDLE_HINT_SECONDS, self._check_idle, args=(self.bypass_detector,))
+                    idle_check.daemon = True
+                    idle_check.start()
                     started = True
                     break
                 self._shutdown_proxy_locked()
@@ -333,23 +2513,50 @@ class ProxyService:
             self._start_in_progress = False
             if not started:
                 self.current_port = None
-                self._status("stopped", f"Failed to start proxy near 127.0.0.1:{port}", port=port)
-                detail = self._last_start_error or "Port may be busy or blocked."
+                self._status("stopped", f"Failed to start proxy near {listen_host}:{port}", port=port)
+                if requested_port_busy and not auto_port:
+                    detail = f"Port {port} is already in use. Choose another port or enable auto port."
+                else:
+                    detail = self._last_start_error or "Port may be busy or blocked."
                 self.event_queue.put(
                     {
                         "type": "error",
-                        "message": f"Proxy failed to start near 127.0.0.1:{port}. {detail}",
+                        "message": f"Proxy failed to start near {listen_host}:{port}. {detail}",
                     }
                 )
 
-    def stop(self):
+    def stop(self, summary=True):
         with self._lock:
+            if summary and self.capture_started_at is not None:
+                self._emit_capture_summary()
+            self.capture_started_at = None
             self.state.capture_enabled.clear()
             self.state.paused.clear()
+            self.state.passthrough.clear()
             self._shutdown_proxy_locked()
             self._status("stopped", "Stopped", port=self.current_port)
             self.current_port = None
 
+    def _emit_capture_summary(self):
+        started_at = self.capture_started_at
+        flows = [r for r in self.flow_store.snapshot() if (r.get("started") or 0) >= started_at]
+        hosts = {}
+        for r in flows:
+            hosts[r.get("host") or ""] = hosts.get(r.get("host") or "", 0) + 1
+        top_hosts = sorted(hosts.items(), key=lambda item: (-item[1], item[0]))[:5]
+        self.event_queue.put(
+            {
+                "type": "capture_summary",
+                "total_flows": len(flows),
+                "total_bytes": sum(
+                    (r.get("request_body_size") or 0) + (r.get("response_body_size") or 0) for r in flows
+                ),
+                "error_count": sum(1 for r in flows if r.get("error") or (r.get("status_code") or 0) >= 400),
+                "top_hosts": [{"host": host, "count": count} for host, count in top_hosts],
+                "duration_ms": int((time.time() - started_at) * 1000),
+            }
+        )
+
     def pause(self):
         with self._lock:
             if not (self.proxy_thread and self.proxy_thread.is_alive()):
@@ -357,15 +2564,1025 @@ class ProxyService:
             self.state.paused.set()
             self._status("paused", "Paused", port=self.current_port)
 
+    def trip_pause(self, message):
+        # Runs on the proxy loop, so it must not take _lock: stop() joins that thread while holding it.
+        self.state.paused.set()
+        self._status("paused", message, port=self.current_port)
+
     def resume(self):
         with self._lock:
             if not (self.proxy_thread and self.proxy_thread.is_alive()):
                 return
             self.state.paused.clear()
-            self._status("running", self._running_message(self.current_port), port=self.current_port)
+            self.event_queue.put(self.current_status_payload())
+
+    def set_passthrough(self, enabled):
+        with self._lock:
+            if not (self.proxy_thread and self.proxy_thread.is_alive()):
+                return
+            if enabled:
+                self.state.passthrough.set()
+            else:
+                self.state.passthrough.clear()
+            self.event_queue.put(self.current_status_payload())
+
+    def mute_hosts(self, patterns):
+        cleaned = {p.strip().lower() for p in patterns if p and p.strip()}
+        self.state.muted_hosts |= cleaned
+        self._confirm(f"Muted hosts: {', '.join(sorted(self.state.muted_hosts)) or 'none'}")
+
+    def unmute_hosts(self, patterns):
+        cleaned = {p.strip().lower() for p in patterns if p and p.strip()}
+        self.state.muted_hosts -= cleaned
+        self._confirm(f"Muted hosts: {', '.join(sorted(self.state.muted_hosts)) or 'none'}")
+
+    def clear_matching(self, patterns):
+        patterns = [p for p in patterns if p and p.strip()]
+        if not patterns:
+            return
+
+        def matches(record):
+            return any(
+                _matches_pattern(record.get("host"), pattern) or _matches_pattern(record.get("path"), pattern)
+                for pattern in patterns
+            )
+
+        removed = self.flow_store.remove_where(lambda r: not r.get("pinned") and matches(r))
+        for flow_id in removed:
+            self.event_queue.put({"type": "flow_evicted", "flow_id": flow_id})
+        self._confirm(f"Cleared {len(removed)} matching flow(s)")
+
+    def clear(self):
+        self.markers = []
+        removed = self.flow_store.remove_where(lambda r: not r.get("pinned"))
+        for flow_id in removed:
+            self.event_queue.put({"type": "flow_evicted", "flow_id": flow_id})
+        self._confirm(f"Cleared {len(removed)} flow(s); pinned flows kept")
+
+    def pin_flow(self, flow_id, pinned):
+        if not self.flow_store.set_pinned(flow_id, pinned):
+            self.event_queue.put({"type": "error", "message": f"Flow {flow_id} is no longer available."})
+            return
+        self.event_queue.put({"type": "flow_pinned", "flow_id": flow_id, "pinned": pinned})
+        self._confirm(f"{'Pinned' if pinned else 'Unpinned'} flow {flow_id}")
+
+    def mark_flow_outcome(self, flow_id, outcome):
+        outcome = outcome.strip() or None
+        if not self.flow_store.set_client_outcome(flow_id, outcome):
+            self.event_queue.put({"type": "error", "message": f"Flow {flow_id} is no longer available."})
+            return
+        self._confirm(f"Flow {flow_id} marked {outcome}" if outcome else f"Client outcome cleared for flow {flow_id}")
+
+    def set_body_capture(self, capture_request, capture_response):
+        self.state.capture_request_body = capture_request
+        self.state.capture_response_body = capture_response
+        enabled = [
+            name
+            for name, on in (("request", capture_request), ("response", capture_response))
+            if on
+        ]
+        self._confirm(f"Capturing bodies: {', '.join(enabled) or 'none'}")
+
+    def set_body_policy(self, store_limit, stream_limit):
+        if store_limit < 0 or stream_limit < 0:
+            self.event_queue.put({"type": "error", "message": "Body limits must not be negative"})
+            return
+        self.state.store_body_limit = store_limit
+        self.state.stream_body_limit = stream_limit
+        self._confirm(f"Bodies: storing up to {store_limit} bytes, streaming up to {stream_limit} bytes")
+
+    def replay_sequence(self, path, base_url=None, preserve_host=False):
+        try:
+            with open(path, "r", encoding="utf-8") as handle:
+                steps = json.load(handle)
+            if not isinstance(steps, list):
+                raise ValueError("expected a JSON list of request templates")
+        except (OSError, ValueError) as exc:
+            self.event_queue.put({"type": "error", "message": f"Sequence file {path} is invalid: {exc}"})
+            return
+        threading.Thread(target=self._run_sequence, args=(path, steps, base_url, preserve_host), daemon=True).start()
+
+    def _run_sequence(self, path, steps, base_url, preserve_host):
+        succeeded = 0
+        for index, step in enumerate(steps, start=1):
+            try:
+                if not isinstance(step, dict) or not step.get("url"):
+                    raise ValueError("step needs at least a url")
+                self.client.send(
+                    step.get("method", "GET"),
+                    _retarget_url(step["url"], base_url),
+                    step.get("headers"),
+                    step.get("body") or b"",
+                    host_header=_original_host(step["url"], preserve_host and base_url),
+                )
+                succeeded += 1
+            except Exception as exc:
+                self.event_queue.put({"type": "error", "message": f"Sequence step {index} failed: {exc}"})
+        self.event_queue.put(
+            {
+                "type": "sequence_complete",
+                "path": path,
+                "total": len(steps),
+                "succeeded": succeeded,
+                "failed": len(steps) - succeeded,
+            }
+        )
+
+    def record_error(self, message):
+        self.last_error = {"message": message, "timestamp": time.time()}
+
+    def report_last_error(self):
+        last_error = self.last_error or {}
+        self.event_queue.put(
+            {
+                "type": "last_error",
+                "message": last_error.get("message"),
+                "timestamp": last_error.get("timestamp"),
+            }
+        )
+
+    def clear_last_error(self):
+        self.last_error = None
+        self.report_last_error()
+
+    def set_sni_override(self, host, sni):
+        host = (host or "").strip().lower()
+        if not host:
+            return
+        if sni and sni.strip():
+            self.state.sni_overrides[host] = sni.strip()
+            self._confirm(f"SNI for {host} set to {sni.strip()}")
+        else:
+            self.state.sni_overrides.pop(host, None)
+            self._confirm(f"SNI override for {host} removed")
+
+    def set_request_timeout(self, seconds):
+        self.state.request_timeout = max(0, seconds)
+        if self.state.request_timeout:
+            self._confirm(f"Request timeout set to {self.state.request_timeout}s")
+        else:
+            self._confirm("Request timeout disabled")
+
+    def set_resolve_timings(self, enabled):
+        self.state.resolve_timings = enabled
+        self._confirm(f"Explicit DNS timing {'enabled' if enabled else 'disabled'}")
+
+    def set_show_preflight(self, enabled):
+        self.state.show_preflight = enabled
+        self._confirm(f"Preflight/OPTIONS requests {'shown' if enabled else 'hidden'}")
+
+    def _stored_request_body(self, record):
+        """The captured request bytes of record; request_body is display text and may be truncated or a placeholder."""
+        bodies = self.flow_store.bodies(record["id"])
+        body = bodies[0] if bodies else b""
+        if not body and record.get("request_body_size"):
+            raise ValueError(f"the request body of flow {record['id']} is no longer stored")
+        return body
+
+    def _replay_record(self, record, url=None, headers=None, body=None, host_header=None, auth=None, source_port=None):
+        headers = record.get("request_headers") if headers is None else headers
+        for name, token in (auth or {}).items():
+            headers = _with_header(headers, name, token)
+        return self.client.send(
+            record.get("method", "GET"),
+            url or record.get("url", ""),
+            headers,
+            self._stored_request_body(record) if body is None else body,
+            host_header=host_header,
+            source_port=source_port,
+        )
+
+    def set_auth_refresh(self, token_header, refresh_flow_id, extract_json_path):
+        token_header = token_header.strip()
+        if not token_header:
+            self.state.auth_refresh = None
+            self._confirm("Auth refresh disabled")
+            return
+        if self.flow_store.get(refresh_flow_id) is None:
+            self.event_queue.put(
+                {"type": "error", "message": f"Auth refresh not set: flow {refresh_flow_id} not found"}
+            )
+            return
+        if not extract_json_path.strip():
+            self.event_queue.put({"type": "error", "message": "Auth refresh not set: JSON path is empty"})
+            return
+        self.state.auth_refresh = (token_header, refresh_flow_id, extract_json_path.strip())
+        self._confirm(f"Replays refresh {token_header} from flow {refresh_flow_id}")
+
+    def _refresh_auth(self):
+        """Re-issues the refresh flow and returns the headers to inject, or None after reporting a failure."""
+        if not self.state.auth_refresh:
+            return {}
+        token_header, flow_id, json_path = self.state.auth_refresh
+        try:
+            record = self.flow_store.get(flow_id)
+            if record is None:
+                raise ValueError(f"refresh flow {flow_id} is no longer available")
+            status, body = self.client.fetch(
+                record.get("method", "GET"),
+                record.get("url", ""),
+                record.get("request_headers"),
+                self._stored_request_body(record),
+            )
+            if status >= 400:
+                raise ValueError(f"refresh flow returned HTTP {status}")
+            try:
+                data = json.loads(body)
+            except ValueError:
+                raise ValueError("refresh response is not JSON") from None
+            return {token_header: _json_path_value(data, json_path)}
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Auth refresh failed, replay skipped: {exc}"})
+            return None
+
+    def replay_flow(self, flow_id):
+        record = self.flow_store.get(flow_id)
+        if record is None:
+            self.event_queue.put({"type": "error", "message": f"Replay failed: flow {flow_id} not found"})
+            return
+        threading.Thread(target=self._run_replay, args=(record,), daemon=True).start()
+
+    def _run_replay(self, record):
+        auth = self._refresh_auth()
+        if auth is None:
+            return
+        headers = list(record.get("request_headers") or [])
+        headers.append({"name": REPLAY_OF_HEADER, "value": record["id"]})
+        try:
+            self._replay_record(record, headers=headers, auth=auth)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Replay of {record['id']} failed: {exc}"})
+
+    def set_fault_rules(self, rules):
+        faults = []
+        for rule in rules:
+            pattern = str(rule.get("url_pattern") or "").strip()
+            fault = str(rule.get("fault") or "")
+            if not pattern:
+                continue
+            if fault not in FAULT_KINDS:
+                self.event_queue.put({"type": "error", "message": f"Unknown fault {fault!r} for {pattern}"})
+                continue
+            faults.append((pattern, fault))
+        self.state.fault_rules = faults
+        self._confirm(f"{len(faults)} fault rule(s) active" if faults else "Fault rules cleared")
+
+    def set_mock_rules(self, rules):
+        mocks = []
+        for rule in rules:
+            pattern = str(rule.get("url_pattern") or "").strip()
+            try:
+                status = int(rule.get("status", 200))
+            except (TypeError, ValueError):
+                status = 0
+            if not pattern:
+                continue
+            if not 100 <= status <= 599:
+                self.event_queue.put({"type": "error", "message": f"Mock rule for {pattern} has invalid status"})
+                continue
+            mocks.append(
+                {
+                    "url_pattern": pattern,
+                    "method": str(rule.get("method") or "").strip().upper() or None,
+                    "status": status,
+                    "headers": [h for h in rule.get("headers") or [] if isinstance(h, dict)],
+                    "body": str(rule.get("body") or ""),
+                    "latency_ms": max(0, int(rule.get("latency_ms") or 0)),
+                    "compress": bool(rule.get("compress", False)),
+                }
+            )
+        self.state.mock_rules = mocks
+        self._confirm(f"{len(mocks)} mock rule(s) active" if mocks else "Mock rules cleared")
+
+    def set_map_local_rules(self, rules):
+        mapped = []
+        for rule in rules:
+            pattern = str(rule.get("url_pattern") or "").strip()
+            local_path = str(rule.get("local_path") or "").strip()
+            if not pattern or not local_path:
+                continue
+            if not os.path.exists(local_path):
+                self.event_queue.put({"type": "error", "message": f"Map-local file not found: {local_path}"})
+                continue
+            mapped.append(
+                {
+                    "url_pattern": pattern,
+                    "local_path": local_path,
+                    "content_type": str(rule.get("content_type") or "").strip() or None,
+                    "compress": bool(rule.get("compress", False)),
+                }
+            )
+        self.state.map_local_rules = mapped
+        self._confirm(f"{len(mapped)} map-local rule(s) active" if mapped else "Map-local rules cleared")
+
+    def set_map_remote_rules(self, rules):
+        mapped = []
+        for rule in rules:
+            pattern = str(rule.get("url_pattern") or "").strip()
+            target_url = str(rule.get("target_url") or "").strip()
+            if not pattern:
+                continue
+            if urlsplit(target_url).scheme not in ("http", "https"):
+                self.event_queue.put(
+                    {"type": "error", "message": f"Map-remote target is not an http(s) URL: {target_url}"}
+                )
+                continue
+            mapped.append(
+                {"url_pattern": pattern, "target_url": target_url, "preserve_host": bool(rule.get("preserve_host"))}
+            )
+        self.state.map_remote_rules = mapped
+        self._confirm(f"{len(mapped)} map-remote rule(s) active" if mapped else "Map-remote rules cleared")
+
+    def set_breakpoints(self, rules):
+        breakpoints = []
+        for rule in rules:
+            pattern = str(rule.get("url_pattern") or "").strip()
+            method = str(rule.get("method") or "").strip().upper()
+            if pattern:
+                breakpoints.append((pattern, method or None))
+        self.state.breakpoints = breakpoints
+        if breakpoints:
+            self._confirm(f"{len(breakpoints)} breakpoint(s) set")
+        else:
+            self._confirm("Breakpoints cleared")
+
+    def resolve_intercepted(self, flow_id, action, changes=None):
+        held = self.state.intercepted.get(flow_id)
+        if held is None:
+            self.event_queue.put({"type": "error", "message": f"Flow {flow_id} is not held by a breakpoint"})
+            return
+        loop, decision = held
+
+        def settle():
+            if not decision.done():
+                decision.set_result((action, changes or {}))
+
+        loop.call_soon_threadsafe(settle)
+
+    def edit_and_replay(self, parent_id, method, url, headers, body):
+        original = self.flow_store.get(parent_id)
+        if original is None:
+            self.event_queue.put({"type": "error", "message": f"Replay failed: flow {parent_id} not found"})
+            return
+        edits = _request_edits(original, method, url, headers, body)
+        if body == (original.get("request_body") or ""):
+            # Unchanged bodies go out as the captured bytes, not the (possibly truncated) display text.
+            bodies = self.flow_store.bodies(parent_id)
+            body = bodies[0] if bodies and bodies[0] else body
+        threading.Thread(
+            target=self._run_edit_replay, args=(original, method, url, headers, body, edits), daemon=True
+        ).start()
+
+    def _run_edit_replay(self, original, method, url, headers, body, edits):
+        auth = self._refresh_auth()
+        if auth is None:
+            return
+        token = secrets.token_hex(8)
+        self.state.replay_edits[token] = edits
+        headers = list(headers) + [
+            {"name": REPLAY_OF_HEADER, "value": original["id"]},
+            {"name": REPLAY_EDIT_HEADER, "value": token},
+        ]
+        try:
+            self._replay_record(dict(original, method=method), url=url, headers=headers, body=body, auth=auth)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Edited replay of {original['id']} failed: {exc}"})
+        finally:
+            # Normally consumed by the new flow; dropped here if the flow was never recorded.
+            self.state.replay_edits.pop(token, None)
+
+    def replay_with_timing(self, flow_ids, speed):
+        records = [self.flow_store.get(flow_id) for flow_id in flow_ids]
+        missing = [flow_id for flow_id, record in zip(flow_ids, records) if record is None]
+        if missing:
+            self.event_queue.put({"type": "error", "message": f"Flows no longer available: {', '.join(missing)}"})
+            return
+        speed = speed if speed and speed > 0 else 1.0
+        records.sort(key=lambda r: r.get("started") or 0)
+        threading.Thread(target=self._run_timed_replay, args=(records, speed), daemon=True).start()
+
+    def _run_timed_replay(self, records, speed):
+        auth = self._refresh_auth()
+        if auth is None:
+            return
+        origin = (records[0].get("started") or 0) if records else 0
+        replay_start = time.monotonic()
+        for index, record in enumerate(records, start=1):
+            due = ((record.get("started") or origin) - origin) / speed
+            delay = due - (time.monotonic() - replay_start)
+            if delay > 0:
+                time.sleep(delay)
+            try:
+                self._replay_record(record, auth=auth)
+            except Exception as exc:
+                self.event_queue.put({"type": "error", "message": f"Replay of {record['id']} failed: {exc}"})
+            self.event_queue.put(
+                {"type": "replay_progress", "flow_id": record["id"], "completed": index, "total": len(records)}
+            )
+
+    def replay_fuzz(self, flow_id, field, mutations):
+        record = self.flow_store.get(flow_id)
+        if record is None:
+            self.event_queue.put({"type": "error", "message": f"Fuzz failed: flow {flow_id} not found"})
+            return
+        try:
+            requests = [_mutate_request(record, field, value) for value in mutations]
+        except (KeyError, IndexError, TypeError, ValueError) as exc:
+            self.event_queue.put({"type": "error", "message": f"Fuzz failed: cannot mutate {field}: {exc}"})
+            return
+        threading.Thread(target=self._run_fuzz, args=(record, field, requests), daemon=True).start()
+
+    def _run_fuzz(self, record, field, requests):
+        auth = self._refresh_auth()
+        if auth is None:
+            return
+        status_counts = {}
+        failed = 0
+        for url, headers, body in requests:
+            try:
+                status = str(self._replay_record(record, url=url, headers=headers, body=body, auth=auth))
+            except Exception:
+                failed += 1
+                continue
+            status_counts[status] = status_counts.get(status, 0) + 1
+        self.event_queue.put(
+            {
+                "type": "fuzz_summary",
+                "flow_id": record["id"],
+                "field": field,
+                "total": len(requests),
+                "status_counts": status_counts,
+                "failed": failed,
+            }
+        )
+
+    def replay_multi(self, flow_id, base_urls, preserve_host=False):
+        record = self.flow_store.get(flow_id)
+        if record is None:
+            self.event_queue.put({"type": "error", "message": f"Replay failed: flow {flow_id} not found"})
+            return
+        base_urls = [url.strip() for url in base_urls if url and url.strip()]
+        if not base_urls:
+            self.event_queue.put({"type": "error", "message": "Replay needs at least one base URL"})
+            return
+        threading.Thread(target=self._run_multi_replay, args=(record, base_urls, preserve_host), daemon=True).start()
+
+    def _run_multi_replay(self, record, base_urls, preserve_host):
+        auth = self._refresh_auth()
+        if auth is None:
+            return
+        results = [{"base_url": url, "status": None, "error": None} for url in base_urls]
+
+        def replay(result):
+            headers = list(record.get("request_headers") or [])
+            headers.append({"name": REPLAY_TARGET_HEADER, "value": result["base_url"]})
+            try:
+                result["status"] = self._replay_record(
+                    record,
+                    url=_retarget_url(record.get("url", ""), result["base_url"]),
+                    headers=headers,
+                    host_header=_original_host(record.get("url", ""), preserve_host),
+                    auth=auth,
+                )
+            except Exception as exc:
+                result["error"] = str(exc)
+
+        workers = [threading.Thread(target=replay, args=(result,), daemon=True) for result in results]
+        for worker in workers:
+            worker.start()
+        for worker in workers:
+            worker.join()
+        self.event_queue.put(
+            {
+                "type": "replay_multi_summary",
+                "flow_id": record["id"],
+                "results": results,
+                "diverged": len({(r["status"], bool(r["error"])) for r in results}) > 1,
+            }
+        )
+
+    def send_raw(self, raw_request, scheme, host, port):
+        try:
+            method, url, headers, body = _parse_raw_request(raw_request, scheme.lower(), host.strip(), port)
+        except ValueError as exc:
+            self.event_queue.put({"type": "error", "message": f"Raw request is malformed: {exc}"})
+            return
+
+        def send():
+            try:
+                self.client.send(method, url, headers, body)
+            except Exception as exc:
+                self.event_queue.put({"type": "error", "message": f"Raw request to {url} failed: {exc}"})
+
+        threading.Thread(target=send, daemon=True).start()
+
+    def send_request(self, method, url, headers, body, direct):
+        if urlsplit(url).scheme not in ("http", "https"):
+            self.event_queue.put({"type": "error", "message": f"Composer needs an http(s) URL, not {url!r}"})
+            return
+        body = body.encode("utf-8")
+        target = self._send_direct if direct else self._send_composed
+        threading.Thread(target=target, args=(method, url, headers, body), daemon=True).start()
+
+    def _send_composed(self, method, url, headers, body):
+        try:
+            self.client.send(method, url, headers, body)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Request to {url} failed: {exc}"})
+
+    def _send_direct(self, method, url, headers, body):
+        # The proxy never sees a direct request, so the record is built and stored here instead.
+        started = time.time()
+        status, response_headers, response_body, error = 0, None, b"", ""
+        try:
+            status, response_headers, response_body = self.client.exchange(method, url, headers, body, direct=True)
+        except Exception as exc:
+            error = str(exc)
+        record = _composed_record(method, url, headers, body, started, status, response_headers, response_body, error)
+        evicted = self.flow_store.add(record, body, response_body)
+        self.flow_tail.write(record)
+        self.event_queue.put({"type": "flow", "record": record})
+        for flow_id in evicted:
+            self.event_queue.put({"type": "flow_evicted", "flow_id": flow_id})
+
+    def capture_handshake(self, host):
+        """Reports upstream TLS failures for host; the handshake itself is recorded on every HTTPS flow regardless."""
+        host = host.strip().lower()
+        if host:
+            self.state.handshake_hosts.add(host)
+            self._confirm(f"Reporting TLS handshake failures for {host}")
+        else:
+            self.state.handshake_hosts.clear()
+            self._confirm("TLS handshake failure reporting disabled")
+
+    def replay_handshake(self, flow_id):
+        record = self.flow_store.get(flow_id)
+        if record is None:
+            self.event_queue.put({"type": "error", "message": f"Replay failed: flow {flow_id} not found"})
+            return
+        handshake = record.get("tls_handshake")
+        if not handshake:
+            self.event_queue.put(
+                {"type": "error", "message": f"Flow {flow_id} has no recorded handshake; it did not use TLS upstream"}
+            )
+            return
+        threading.Thread(target=self._run_handshake_replay, args=(record, handshake), daemon=True).start()
+
+    def _run_handshake_replay(self, record, handshake):
+        auth = self._refresh_auth()
+        if auth is None:
+            return
+        # The pin is keyed to this replay's own proxy connection, so live traffic and other replays
+        # to the same host keep their normal handshakes.
+        client = (LOOPBACK_HOST, _ephemeral_port(LOOPBACK_HOST))
+        self.state.handshake_pins[client] = handshake
+        try:
+            self._replay_record(record, auth=auth, source_port=client[1])
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Handshake replay of {record['id']} failed: {exc}"})
+        finally:
+            self.state.handshake_pins.pop(client, None)
+
+    def set_strip_accept_encoding(self, enabled):
+        self.state.strip_accept_encoding = enabled
+        self._confirm(f"Accept-Encoding stripping {'enabled' if enabled else 'disabled'}")
+
+    def set_brotli_decode(self, enabled):
+        self.state.brotli_decode = enabled
+        self._confirm(f"Brotli decoding {'enabled' if enabled else 'disabled (br bodies kept raw)'}")
+
+    def set_user_agent(self, value):
+        self.state.user_agent = value or None
+        if self.state.user_agent:
+            self._confirm(f"User-Agent overridden: {self.state.user_agent}")
+        else:
+            self._confirm("User-Agent passed through unchanged")
+
+    def set_connection_reuse(self, enabled):
+        self.state.connection_reuse = enabled
+        self._confirm(f"Upstream connection reuse {'enabled' if enabled else 'disabled'}")
+
+    def _connection_strategy(self):
+        # Lazy upstream connects keep record-only mode from ever dialing the real server.
+        return "lazy" if self.state.record_only_status else "eager"
+
+    def set_record_only(self, canned_status):
+        self.state.record_only_status = canned_status if 100 <= canned_status <= 599 else 0
+        master, loop = self.proxy_master, self.proxy_loop
+        if master is not None and loop is not None:
+            strategy = self._connection_strategy()
+            loop.call_soon_threadsafe(lambda: master.options.update(connection_strategy=strategy))
+        self.event_queue.put(self.current_status_payload())
+
+    def set_host_throttles(self, patterns, down_kbps, latency_ms):
+        patterns = [p.strip().lower() for p in patterns if p and p.strip()]
+        if not patterns or not (down_kbps or latency_ms):
+            self.event_queue.put({"type": "error", "message": "Host throttle needs patterns and a limit"})
+            return
+        for pattern in patterns:
+            self.state.host_throttles[pattern] = (down_kbps or None, latency_ms or None)
+        limits = [f"{down_kbps} kbps down" if down_kbps else "", f"{latency_ms} ms latency" if latency_ms else ""]
+        self._confirm(f"Throttling {', '.join(patterns)}: {', '.join(filter(None, limits))}")
+
+    def clear_host_throttles(self):
+        self.state.host_throttles.clear()
+        self._confirm("Host throttles cleared")
+
+    def set_throttle(self, download_kbps, upload_kbps, latency_ms):
+        limits = (max(0, download_kbps), max(0, upload_kbps), max(0, latency_ms))
+        self.state.network_throttle = limits
+        if not any(limits):
+            self._confirm("Network throttle off")
+            return
+        down, up, latency = limits
+        parts = [
+            f"{down} kbps down" if down else "",
+            f"{up} kbps up" if up else "",
+            f"{latency} ms latency" if latency else "",
+        ]
+        self._confirm(f"Network throttle: {', '.join(filter(None, parts))}")
+
+    def set_latency_by_status(self, rules):
+        try:
+            parsed = {int(status): int(latency_ms) for status, latency_ms in rules}
+        except (TypeError, ValueError):
+            self.event_queue.put({"type": "error", "message": "Latency rules must be [status, latency_ms] pairs"})
+            return
+        invalid = [status for status in parsed if not 100 <= status <= 599]
+        if invalid:
+            self.event_queue.put({"type": "error", "message": f"Invalid status codes: {invalid}"})
+            return
+        self.state.status_latency.update({status: ms for status, ms in parsed.items() if ms > 0})
+        for status in [status for status, ms in parsed.items() if ms <= 0]:
+            self.state.status_latency.pop(status, None)
+        rules_text = ", ".join(f"{status}: {ms} ms" for status, ms in sorted(self.state.status_latency.items()))
+        self._confirm(f"Latency by status: {rules_text or 'none'}")
+
+    def clear_latency_rules(self):
+        self.state.status_latency.clear()
+        self._confirm("Latency rules cleared")
+
+    def set_dns_servers(self, servers):
+        try:
+            parsed = [_parse_dns_server(server) for server in servers if server and server.strip()]
+        except ValueError as exc:
+            self.event_queue.put({"type": "error", "message": f"Invalid DNS server: {exc}"})
+            return
+        self.state.dns_servers = parsed
+        if parsed:
+            self._confirm(f"Resolving upstream hosts via {', '.join(ip for ip, _ in parsed)}")
+        else:
+            self._confirm("Resolving upstream hosts via the system resolver")
+
+    def scope_to_client(self, token):
+        self.state.scoped_clients.clear()
+        self.state.client_scope_token = token.strip() or None
+        if self.state.client_scope_token:
+            self._confirm("Capturing only the launched browser's traffic")
+        else:
+            self._confirm("Capturing traffic from all clients")
+
+    def set_process_filter(self, pids, names):
+        self.state.process_clients.clear()
+        names = {name.strip().lower().removesuffix(".exe") for name in names if name.strip()}
+        if pids or names:
+            self.state.process_filter = (set(pids), names)
+            listed = ", ".join([str(pid) for pid in sorted(pids)] + sorted(names))
+            self._confirm(f"Capturing only traffic from new connections by: {listed}")
+        else:
+            self.state.process_filter = None
+            self._confirm("Capturing traffic from all processes")
+
+    def set_pause_on_error(self, enabled, status_min):
+        self.state.pause_on_error = enabled
+        self.state.pause_status_min = status_min or None
+        if not enabled:
+            self._confirm("Pause on error disabled")
+        elif status_min:
+            self._confirm(f"Pausing on the first error or status >= {status_min}")
+        else:
+            self._confirm("Pausing on the first failed flow")
+
+    def set_force_http1(self, hosts):
+        self.state.http1_hosts = {h.strip().lower() for h in hosts if h.strip()}
+        if self.state.http1_hosts:
+            self._confirm(f"Forcing HTTP/1.1 upstream for: {', '.join(sorted(self.state.http1_hosts))}")
+        else:
+            self._confirm("HTTP/1.1 no longer forced for any host")
+
+    def set_dedupe_by_endpoint(self, keep):
+        keep = keep.strip().lower()
+        if keep not in ("first", "last", "all", ""):
+            self.event_queue.put({"type": "error", "message": f"Dedupe keep must be first, last or all, not {keep}"})
+            return
+        self.state.endpoint_flows.clear()
+        self.state.dedupe_keep = keep if keep in ("first", "last") else None
+        if self.state.dedupe_keep:
+            self._confirm(f"Keeping only the {keep} flow per endpoint")
+        else:
+            self._confirm("Capturing every occurrence of each endpoint")
+
+    def set_capture_tls_fingerprints(self, enabled):
+        self.state.capture_tls_fingerprints = enabled
+        if not enabled:
+            self.state.client_fingerprints.clear()
+        self._confirm(f"TLS fingerprint capture {'enabled' if enabled else 'disabled'}")
+
+    def set_capture_client_hello(self, enabled):
+        self.state.capture_client_hello = enabled
+        if not enabled:
+            self.state.client_hellos.clear()
+        self._confirm(f"ClientHello capture {'enabled' if enabled else 'disabled'}")
+
+    def set_port_filter(self, ports):
+        self.state.allowed_ports = set(ports)
+        if ports:
+            self._confirm(f"Capturing only ports {', '.join(str(p) for p in sorted(ports))}")
+        else:
+            self._confirm("Capturing all ports")
+
+    def add_marker(self, label):
+        marker = {"timestamp": time.time(), "label": label}
+        self.markers.append(marker)
+        self.event_queue.put({"type": "marker", **marker})
+
+    def report_sidecar_info(self):
+        master = self.proxy_master
+        addons = [type(addon).__name__ for addon in master.addons.chain] if master is not None else []
+        self.event_queue.put(
+            {
+                "type": "sidecar_info",
+                "mitmproxy_version": version.VERSION,
+                "python_version": platform.python_version(),
+                "platform": platform.platform(),
+                "frozen": bool(getattr(sys, "frozen", False)),
+                "loaded_addons": addons,
+            }
+        )
+
+    def report_memory_usage(self):
+        flow_count, total_body_bytes = self.flow_store.stats()
+        self.event_queue.put(
+            {
+                "type": "memory_usage",
+                "rss_bytes": _process_rss_bytes(),
+                "flow_count": flow_count,
+                "total_body_bytes": total_body_bytes,
+            }
+        )
+
+    def export_waterfall(self, path):
+        try:
+            target = _validate_output_path(path)
+            flows = sorted(self.flow_store.snapshot(), key=lambda r: r.get("started") or 0)
+            origin = flows[0]["started"] if flows else time.time()
+            entries = [
+                {
+                    "flow_id": r["id"],
+                    "host": r.get("host", ""),
+                    "start_offset_ms": max(0, int(((r.get("started") or origin) - origin) * 1000)),
+                    "duration_ms": r.get("duration_ms", 0),
+                    "status": r.get("status_code", 0),
+                }
+                for r in flows
+            ]
+            with open(target, "w", encoding="utf-8") as handle:
+                json.dump(
+                    {
+                        "origin_iso": _iso_time(origin),
+                        "entries": entries,
+                        "markers": [
+                            {
+                                "label": m["label"],
+                                "start_offset_ms": max(0, int((m["timestamp"] - origin) * 1000)),
+                            }
+                            for m in self.markers
+                        ],
+                    },
+                    handle,
+                    separators=(",", ":"),
+                )
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Waterfall export failed: {exc}"})
+            return
+        self._export_complete("waterfall", target, len(entries))
+
+    def _filtered_flows(self, filter_text=None):
+        flows = self.flow_store.snapshot()
+        if not filter_text or not filter_text.strip():
+            return flows
+        return [
+            r
+            for r in flows
+            if _matches_pattern(r.get("url"), filter_text) or _matches_pattern(r.get("host"), filter_text)
+        ]
+
+    def export_postman(self, path, filter_text=None):
+        try:
+            target = _validate_output_path(path)
+            folders = OrderedDict()
+            count = 0
+            for r in self._filtered_flows(filter_text):
+                folder = folders.setdefault(r.get("host") or "unknown", {"items": [], "names": {}})
+                base_name = f"{r.get('method', 'GET')} {(r.get('path') or '/').split('?')[0]}"
+                seen = folder["names"].get(base_name, 0) + 1
+                folder["names"][base_name] = seen
+                request = {
+                    "method": r.get("method", "GET"),
+                    "header": [
+                        {"key": h["name"], "value": h["value"]}
+                        for h in r.get("request_headers") or []
+                        if h["name"].lower() not in REPLAY_SKIPPED_HEADERS
+                    ],
+                    "url": {"raw": r.get("url", "")},
+                }
+                if r.get("request_body"):
+                    request["body"] = {"mode": "raw", "raw": r["request_body"]}
+                folder["items"].append(
+                    {"name": base_name if seen == 1 else f"{base_name} ({seen})", "request": request}
+                )
+                count += 1
+            collection = {
+                "info": {
+                    "name": f"PacketLens capture {datetime.now().strftime('%Y-%m-%d %H:%M')}",
+                    "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json",
+                },
+                "item": [{"name": host, "item": folder["items"]} for host, folder in folders.items()],
+            }
+            with open(target, "w", encoding="utf-8") as handle:
+                json.dump(collection, handle, indent=2)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Postman export failed: {exc}"})
+            return
+        self._export_complete("postman", target, count)
+
+    def _baseline_diff(self, baseline_path):
+        try:
+            baseline = _load_baseline(baseline_path)
+        except (OSError, ValueError) as exc:
+            self.event_queue.put({"type": "error", "message": f"Baseline {baseline_path} is invalid: {exc}"})
+            return None
+        return _diff_endpoints(_endpoint_summary(baseline), _endpoint_summary(self.flow_store.snapshot()))
+
+    def compare_baseline(self, baseline_path):
+        diff = self._baseline_diff(baseline_path)
+        if diff is not None:
+            self.event_queue.put({"type": "baseline_diff", "baseline_path": baseline_path, **diff})
+
+    def export_session_diff(self, baseline_path, out_path):
+        diff = self._baseline_diff(baseline_path)
+        if diff is None:
+            return
+        try:
+            target = _validate_output_path(out_path)
+            with open(target, "w", encoding="utf-8") as handle:
+                handle.write(_diff_report(baseline_path, diff))
+            # The machine-readable copy sits next to the report.
+            with open(os.path.splitext(target)[0] + ".json", "w", encoding="utf-8") as handle:
+                json.dump({"baseline_path": baseline_path, **diff}, handle, indent=2)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Session diff export failed: {exc}"})
+            return
+        self.event_queue.put({"type": "baseline_diff", "baseline_path": baseline_path, **diff})
+        self._export_complete("session_diff", target, len(diff["added"]) + len(diff["removed"]) + len(diff["changed"]))
+
+    def start_tail(self, host_pattern, path):
+        if not host_pattern.strip():
+            self.event_queue.put({"type": "error", "message": "Tail needs a host pattern"})
+            return
+        try:
+            target = _validate_output_path(path)
+            self.flow_tail.start(host_pattern.strip(), target)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Tail failed to start: {exc}"})
+            return
+        self.event_queue.put({"type": "service_state", "service": "tail", "running": True, "address": target})
+
+    def stop_tail(self):
+        self.flow_tail.stop()
+        self.event_queue.put({"type": "service_state", "service": "tail", "running": False, "address": None})
+
+    def set_proto_descriptor(self, descriptor_path):
+        try:
+            self.state.proto_pool = _load_proto_pool(descriptor_path, self.state.proto_pool)
+        except ImportError:
+            self.event_queue.put({"type": "error", "message": "Protobuf decoding needs the protobuf package"})
+            return
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Descriptor {descriptor_path} is invalid: {exc}"})
+            return
+        self._confirm(f"Protobuf descriptor loaded from {os.path.basename(descriptor_path)}")
+
+    def decode_proto(self, flow_id):
+        record = self.flow_store.get(flow_id)
+        if record is None:
+            self.event_queue.put({"type": "error", "message": f"Decode failed: flow {flow_id} not found"})
+            return
+        if self.state.proto_pool is None:
+            self.event_queue.put({"type": "error", "message": "Decode failed: no protobuf descriptor loaded"})
+            return
+        request_body, response_body = self.flow_store.bodies(flow_id) or (b"", b"")
+        try:
+            _grpc_method(self.state.proto_pool, record.get("url", ""))
+        except ValueError as exc:
+            self.event_queue.put({"type": "error", "message": f"Decode failed: {exc}"})
+            return
+        decoded = _decode_grpc_flow(self.state.proto_pool, record, request_body, response_body)
+        self.event_queue.put({"type": "decoded_body", "flow_id": flow_id, "json": decoded})
+
+    def get_hexdump(self, flow_id, direction, offset, length):
+        if direction not in ("request", "response"):
+            self.event_queue.put(
+                {"type": "error", "message": f"Hexdump direction must be request or response, not {direction}"}
+            )
+            return
+        if self.flow_store.get(flow_id) is None:
+            self.event_queue.put({"type": "error", "message": f"Hexdump failed: flow {flow_id} not found"})
+            return
+        bodies = self.flow_store.bodies(flow_id)
+        body = (bodies[0] if direction == "request" else bodies[1]) if bodies else b""
+        if not body:
+            self.event_queue.put(
+                {"type": "error", "message": f"Hexdump failed: no full {direction} body is retained for this flow"}
+            )
+            return
+        offset = max(0, offset)
+        window = body[offset : offset + min(max(0, length), MAX_HEXDUMP_BYTES)]
+        self.event_queue.put(
+            {"type": "hexdump", "flow_id": flow_id, "offset": offset, "lines": _hexdump_lines(window, offset)}
+        )
+
+    def save_response_body(self, flow_id, path):
+        if self.flow_store.get(flow_id) is None:
+            self.event_queue.put({"type": "error", "message": f"Save failed: flow {flow_id} not found"})
+            return
+        bodies = self.flow_store.bodies(flow_id)
+        body = bodies[1] if bodies else None
+        if not body:
+            self.event_queue.put(
+                {"type": "error", "message": "Save failed: no full response body is retained for this flow"}
+            )
+            return
+        try:
+            target = _validate_output_path(path)
+            with open(target, "wb") as handle:
+                handle.write(body)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Save failed: {exc}"})
+            return
+        self._export_complete("response_body", target, len(body))
+
+    def export_openapi(self, path, filter_text=None):
+        try:
+            target = _validate_output_path(path)
+            flows = self._filtered_flows(filter_text)
+            spec = _build_openapi(flows)
+            with open(target, "w", encoding="utf-8") as handle:
+                json.dump(spec, handle, indent=2)
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"OpenAPI export failed: {exc}"})
+            return
+        self._export_complete("openapi", target, len(flows))
+
+    def export_redacted_har(self, path, header_names, body_patterns):
+        try:
+            target = _validate_output_path(path)
+            records, redacted = _redact_records(self.flow_store.snapshot(), header_names, body_patterns)
+            with open(target, "w", encoding="utf-8") as handle:
+                json.dump(_build_har(records, self.markers), handle, indent=2)
+        except re.error as exc:
+            self.event_queue.put({"type": "error", "message": f"Invalid redaction pattern: {exc}"})
+            return
+        except Exception as exc:
+            self.event_queue.put({"type": "error", "message": f"Redacted HAR export failed: {exc}"})
+            return
+        self._export_complete("redacted_har", target, len(records), redacted=redacted)
+
+    def _export_complete(self, kind, path, count, redacted=None):
+        self.event_queue.put(
+            {"type": "export_complete", "kind": kind, "path": path, "count": count, "redacted": redacted}
+        )
+
+    def _check_idle(self, detector):
+        # A restart replaces the detector, so stale timers fall through here.
+        if detector is self.bypass_detector and self.proxy_thread and self.proxy_thread.is_alive():
+            detector.check_idle()
+
+    def mark_degraded(self, source, detail):
+        self._degraded[source] = detail
+        self.event_queue.put(self.current_status_payload())
+
+    def clear_degraded(self, source):
+        if self._degraded.pop(source, None) is not None:
+            self.event_queue.put(self.current_status_payload())
+
+    def _confirm(self, message):
+        payload = self.current_status_payload()
+        payload["message"] = message
+        self.event_queue.put(payload)
 
     def _status(self, status, message, port=None):
-        self.event_queue.put({"type": "status", "status": status, "message": message, "port": port})
+        self.event_queue.put(self._status_payload(status, message, port))
 
     def _shutdown_proxy_locked(self):
         if self.proxy_master is not None:
@@ -383,6 +3600,236 @@ class ProxyService:
         self.proxy_thread = None
 
 
+class HttpBridge:
+    """Streams flow events to external consumers as Server-Sent Events."""
+
+    def __init__(self, host=LOOPBACK_HOST):
+        self.host = host
+        self.server = None
+        self.port = None
+        self.clients = set()
+
+    @property
+    def running(self):
+        return self.server is not None
+
+    async def start(self, port):
+        await self.stop()
+        self.server = await asyncio.start_server(self._handle_client, self.host, port)
+        self.port = port
+
+    async def stop(self):
+        if self.server is None:
+            return
+        self.server.close()
+        for writer in list(self.clients):
+            writer.close()
+        self.clients.clear()
+        try:
+            await self.server.wait_closed()
+        except Exception:
+            pass
+        self.server = None
+        self.port = None
+
+    async def _handle_client(self, reader, writer):
+        try:
+            # Every path streams the same feed, so the request head is only drained.
+            while True:
+                line = await reader.readline()
+                if not line or line in (b"\r\n", b"\n"):
+                    break
+            writer.write(
+                b"HTTP/1.1 200 OK\r\n"
+                b"Content-Type: text/event-stream\r\n"
+                b"Cache-Control: no-cache\r\n"
+                b"Connection: keep-alive\r\n"
+                b"Access-Control-Allow-Origin: *\r\n\r\n"
+            )
+            await writer.drain()
+            self.clients.add(writer)
+            await reader.read()
+        except Exception:
+            pass
+        finally:
+            self.clients.discard(writer)
+            try:
+                writer.close()
+                await writer.wait_closed()
+            except Exception:
+                pass
+
+    async def publish(self, payload):
+        if not self.clients or payload.get("type") != "flow":
+            return
+        frame = f"event: flow\ndata: {json.dumps(payload)}\n\n".encode("utf-8")
+        dead = []
+        for writer in self.clients:
+            try:
+                writer.write(frame)
+                await writer.drain()
+            except Exception:
+                dead.append(writer)
+        for writer in dead:
+            self.clients.discard(writer)
+
+
+class SimpleHttpEndpoint:
+    """Minimal one-shot HTTP server; subclasses map a request path to a response."""
+
+    def __init__(self, host=LOOPBACK_HOST):
+        self.host = host
+        self.server = None
+        self.port = None
+
+    async def start(self, port):
+        await self.stop()
+        self.server = await asyncio.start_server(self._handle_client, self.host, port)
+        self.port = port
+
+    async def stop(self):
+        if self.server is None:
+            return
+        self.server.close()
+        try:
+            await self.server.wait_closed()
+        except Exception:
+            pass
+        self.server = None
+        self.port = None
+
+    def _response(self, path):
+        raise NotImplementedError
+
+    async def _handle_client(self, reader, writer):
+        try:
+            request_line = await reader.readline()
+            while True:
+                line = await reader.readline()
+                if not line or line in (b"\r\n", b"\n"):
+                    break
+            parts = request_line.decode("latin-1").split()
+            response = self._response(parts[1].split("?")[0]) if len(parts) >= 2 else None
+            if response is None:
+                response = ("404 Not Found", "text/plain", b"Not Found\n")
+            status, content_type, body = response
+            head = (
+                f"HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\n"
+                f"Content-Length: {len(body)}\r\nConnection: close\r\n\r\n"
+            )
+            writer.write(head.encode("latin-1") + body)
+            await writer.drain()
+        except Exception:
+            pass
+        finally:
+            try:
+                writer.close()
+                await writer.wait_closed()
+            except Exception:
+                pass
+
+
+class MetricsEndpoint(SimpleHttpEndpoint):
+    """Counts flow events and serves them as Prometheus text on /metrics."""
+
+    def __init__(self, host=LOOPBACK_HOST):
+        super().__init__(host)
+        self.flows = 0
+        self.request_bytes = 0
+        self.response_bytes = 0
+        self.errors = 0
+        self.status_classes = {}
+
+    def observe(self, payload):
+        if payload.get("type") != "flow":
+            return
+        record = payload.get("record") or {}
+        self.flows += 1
+        self.request_bytes += record.get("request_body_size") or 0
+        self.response_bytes += record.get("response_body_size") or 0
+        if record.get("error"):
+            self.errors += 1
+        status = record.get("status_code") or 0
+        status_class = f"{status // 100}xx" if status else "none"
+        self.status_classes[status_class] = self.status_classes.get(status_class, 0) + 1
+
+    def render(self):
+        lines = [
+            "# TYPE packetlens_flows_total counter",
+            f"packetlens_flows_total {self.flows}",
+            "# TYPE packetlens_body_bytes_total counter",
+            f'packetlens_body_bytes_total{{direction="request"}} {self.request_bytes}',
+            f'packetlens_body_bytes_total{{direction="response"}} {self.response_bytes}',
+            "# TYPE packetlens_errors_total counter",
+            f"packetlens_errors_total {self.errors}",
+            "# TYPE packetlens_responses_total counter",
+        ]
+        lines += [
+            f'packetlens_responses_total{{status_class="{name}"}} {count}'
+            for name, count in sorted(self.status_classes.items())
+        ]
+        return "\n".join(lines) + "\n"
+
+    def _response(self, path):
+        if path != "/metrics":
+            return None
+        return "200 OK", "text/plain; version=0.0.4", self.render().encode("utf-8")
+
+
+def _pac_host_patterns(patterns):
+    """Lowercased PAC host patterns; a leading "*." is dropped since bare names cover subdomains."""
+    cleaned = (str(p).strip().lower() for p in patterns or [])
+    return [p[2:] if p.startswith("*.") else p for p in cleaned if p and p != "*."]
+
+
+def _pac_host_test(patterns):
+    """JavaScript condition matching host against names, their subdomains, or shell globs."""
+    tests = []
+    for pattern in patterns:
+        if "*" in pattern or "?" in pattern:
+            tests.append(f"shExpMatch(host, {json.dumps(pattern)})")
+        else:
+            tests.append(f"host == {json.dumps(pattern)} || dnsDomainIs(host, {json.dumps('.' + pattern)})")
+    return " || ".join(tests)
+
+
+class PacServer(SimpleHttpEndpoint):
+    """Serves a proxy auto-config file that points clients at the running proxy."""
+
+    def __init__(self, proxy_service):
+        super().__init__(proxy_service.current_host)
+        self.proxy_service = proxy_service
+        self.include = []
+        self.exclude = []
+
+    def proxy_address(self):
+        host = self.proxy_service.current_host
+        if host == ALL_INTERFACES_HOST:
+            host = _lan_address()
+        return f"{host}:{self.proxy_service.current_port}" if self.proxy_service.current_port else None
+
+    def render(self):
+        address = self.proxy_address()
+        bypass = " || ".join(f'host == "{h}"' for h in PAC_BYPASS_HOSTS)
+        target = f'"PROXY {address}; DIRECT"' if address else '"DIRECT"'
+        lines = [
+            "function FindProxyForURL(url, host) {",
+            "  host = host.toLowerCase();",
+            f'  if ({bypass}) return "DIRECT";',
+        ]
+        if self.exclude:
+            lines.append(f'  if ({_pac_host_test(self.exclude)}) return "DIRECT";')
+        if self.include:
+            lines.append(f'  if (!({_pac_host_test(self.include)})) return "DIRECT";')
+        lines += [f"  return {target};", "}"]
+        return "\n".join(lines) + "\n"
+
+    def _response(self, path):
+        if path not in ("/", "/proxy.pac"):
+            return None
+        return "200 OK", "application/x-ns-proxy-autoconfig", self.render().encode("utf-8")
+
+
 class IpcServer:
     def __init__(self, host, port, proxy_service):
         self.host = host
@@ -390,9 +3837,13 @@ class IpcServer:
         self.proxy_service = proxy_service
         self.event_queue = proxy_service.event_queue
         self.clients = set()
+        self.http_bridge = HttpBridge()
+        self.metrics = MetricsEndpoint()
+        self.pac_server = PacServer(proxy_service)
 
     async def start(self):
         server = await asyncio.start_server(self._handle_client, self.host, self.port)
+        self.proxy_service.ipc_port = server.sockets[0].getsockname()[1]
         async with server:
             await server.serve_forever()
 
@@ -421,15 +3872,294 @@ class IpcServer:
         msg_type = msg.get("type")
         if msg_type == "start":
             port = int(msg.get("port", 8080))
-            self.proxy_service.start(port)
+            self.proxy_service.start(
+                port,
+                msg.get("bind_scope", "loopback"),
+                bool(msg.get("auto_port", False)),
+                bool(msg.get("http3", False)),
+                [str(p) for p in msg.get("transparent_processes") or []],
+                bool(msg.get("confirm_lan", False)),
+            )
         elif msg_type == "stop":
-            self.proxy_service.stop()
+            self.proxy_service.stop(summary=not msg.get("skip_summary", False))
         elif msg_type == "pause":
             self.proxy_service.pause()
         elif msg_type == "resume":
             self.proxy_service.resume()
+        elif msg_type == "passthrough":
+            self.proxy_service.set_passthrough(bool(msg.get("enabled", False)))
+        elif msg_type == "mute_hosts":
+            self.proxy_service.mute_hosts(msg.get("patterns") or [])
+        elif msg_type == "unmute_hosts":
+            self.proxy_service.unmute_hosts(msg.get("patterns") or [])
+        elif msg_type == "clear":
+            self.proxy_service.clear()
+        elif msg_type == "pin_flow":
+            self.proxy_service.pin_flow(str(msg.get("flow_id", "")), bool(msg.get("pinned", True)))
+        elif msg_type == "mark_flow_outcome":
+            self.proxy_service.mark_flow_outcome(str(msg.get("flow_id", "")), str(msg.get("outcome") or ""))
+        elif msg_type == "clear_matching":
+            self.proxy_service.clear_matching(msg.get("patterns") or [])
+        elif msg_type == "body_capture_toggles":
+            self.proxy_service.set_body_capture(
+                bool(msg.get("capture_request", True)),
+                bool(msg.get("capture_response", True)),
+            )
+        elif msg_type == "set_body_policy":
+            self.proxy_service.set_body_policy(
+                int(msg.get("store_limit", MAX_RETAINED_BODY_BYTES)),
+                int(msg.get("stream_limit", MAX_BODY_CAPTURE)),
+            )
+        elif msg_type == "replay_sequence":
+            self.proxy_service.replay_sequence(
+                msg.get("path", ""), msg.get("base_url"), bool(msg.get("preserve_host", False))
+            )
+        elif msg_type == "get_last_error":
+            self.proxy_service.report_last_error()
+        elif msg_type == "clear_last_error":
+            self.proxy_service.clear_last_error()
+        elif msg_type == "sni_override":
+            self.proxy_service.set_sni_override(msg.get("host", ""), msg.get("sni", ""))
+        elif msg_type == "set_request_timeout":
+            self.proxy_service.set_request_timeout(int(msg.get("seconds", 0)))
+        elif msg_type == "resolve_timings":
+            self.proxy_service.set_resolve_timings(bool(msg.get("enabled", False)))
+        elif msg_type == "show_preflight":
+            self.proxy_service.set_show_preflight(bool(msg.get("enabled", True)))
+        elif msg_type == "replay_with_timing":
+            self.proxy_service.replay_with_timing(
+                [str(flow_id) for flow_id in msg.get("flow_ids") or []],
+                float(msg.get("speed", 1.0)),
+            )
+        elif msg_type == "replay_fuzz":
+            self.proxy_service.replay_fuzz(
+                msg.get("flow_id", ""),
+                str(msg.get("field", "")),
+                [str(value) for value in msg.get("mutations") or []],
+            )
+        elif msg_type == "replay_multi":
+            self.proxy_service.replay_multi(
+                msg.get("flow_id", ""),
+                [str(url) for url in msg.get("base_urls") or []],
+                bool(msg.get("preserve_host", False)),
+            )
+        elif msg_type == "send_raw":
+            self.proxy_service.send_raw(
+                str(msg.get("raw_request", "")),
+                str(msg.get("scheme", "https")),
+                str(msg.get("host", "")),
+                int(msg.get("port", 0)),
+            )
+        elif msg_type == "capture_handshake":
+            self.proxy_service.capture_handshake(str(msg.get("host", "")))
+        elif msg_type == "replay_handshake":
+            self.proxy_service.replay_handshake(msg.get("flow_id", ""))
+        elif msg_type == "set_fault_rules":
+            self.proxy_service.set_fault_rules([r for r in msg.get("rules") or [] if isinstance(r, dict)])
+        elif msg_type == "set_mock_rules":
+            self.proxy_service.set_mock_rules([r for r in msg.get("rules") or [] if isinstance(r, dict)])
+        elif msg_type == "set_map_local_rules":
+            self.proxy_service.set_map_local_rules([r for r in msg.get("rules") or [] if isinstance(r, dict)])
+        elif msg_type == "set_map_remote_rules":
+            self.proxy_service.set_map_remote_rules([r for r in msg.get("rules") or [] if isinstance(r, dict)])
+        elif msg_type == "set_breakpoints":
+            self.proxy_service.set_breakpoints([r for r in msg.get("rules") or [] if isinstance(r, dict)])
+        elif msg_type == "resume_intercepted":
+            self.proxy_service.resolve_intercepted(str(msg.get("flow_id", "")), "resume")
+        elif msg_type == "modify_intercepted":
+            self.proxy_service.resolve_intercepted(
+                str(msg.get("flow_id", "")),
+                "modify",
+                {key: msg.get(key) for key in ("method", "url", "headers", "body")},
+            )
+        elif msg_type == "drop_intercepted":
+            self.proxy_service.resolve_intercepted(str(msg.get("flow_id", "")), "drop")
+        elif msg_type == "edit_and_replay":
+            self.proxy_service.edit_and_replay(
+                str(msg.get("parent_id", "")),
+                str(msg.get("method", "GET")).upper(),
+                str(msg.get("url", "")),
+                msg.get("headers") or [],
+                str(msg.get("body") or ""),
+            )
+        elif msg_type == "send_request":
+            self.proxy_service.send_request(
+                str(msg.get("method", "GET")).upper(),
+                str(msg.get("url", "")),
+                msg.get("headers") or [],
+                str(msg.get("body") or ""),
+                bool(msg.get("direct", False)),
+            )
+        elif msg_type == "replay_flow":
+            self.proxy_service.replay_flow(str(msg.get("id", "")))
+        elif msg_type == "set_auth_refresh":
+            self.proxy_service.set_auth_refresh(
+                str(msg.get("token_header") or ""),
+                str(msg.get("refresh_flow_id") or ""),
+                str(msg.get("extract_json_path") or ""),
+            )
+        elif msg_type == "strip_accept_encoding":
+            self.proxy_service.set_strip_accept_encoding(bool(msg.get("enabled", False)))
+        elif msg_type == "capture_tls_fingerprints":
+            self.proxy_service.set_capture_tls_fingerprints(bool(msg.get("enabled", False)))
+        elif msg_type == "capture_client_hello":
+            self.proxy_service.set_capture_client_hello(bool(msg.get("enabled", False)))
+        elif msg_type == "port_filter":
+            ports = [int(p) for p in msg.get("allowed_ports") or [] if 0 < int(p) < 65536]
+            self.proxy_service.set_port_filter(ports)
+        elif msg_type == "brotli_decode":
+            self.proxy_service.set_brotli_decode(bool(msg.get("enabled", True)))
+        elif msg_type == "set_user_agent":
+            self.proxy_service.set_user_agent(msg.get("value"))
+        elif msg_type == "connection_reuse":
+            self.proxy_service.set_connection_reuse(bool(msg.get("enabled", True)))
+        elif msg_type == "record_only":
+            self.proxy_service.set_record_only(int(msg.get("canned_status", 0)))
+        elif msg_type == "throttle_hosts":
+            self.proxy_service.set_host_throttles(
+                [str(p) for p in msg.get("patterns") or []],
+                int(msg.get("down_kbps") or 0),
+                int(msg.get("latency_ms") or 0),
+            )
+        elif msg_type == "clear_host_throttles":
+            self.proxy_service.clear_host_throttles()
+        elif msg_type == "set_throttle":
+            self.proxy_service.set_throttle(
+                int(msg.get("download_kbps") or 0),
+                int(msg.get("upload_kbps") or 0),
+                int(msg.get("latency_ms") or 0),
+            )
+        elif msg_type == "latency_by_status":
+            self.proxy_service.set_latency_by_status(msg.get("rules") or [])
+        elif msg_type == "clear_latency_rules":
+            self.proxy_service.clear_latency_rules()
+        elif msg_type == "set_dns_servers":
+            self.proxy_service.set_dns_servers([str(server) for server in msg.get("servers") or []])
+        elif msg_type == "scope_to_client":
+            self.proxy_service.scope_to_client(str(msg.get("token") or ""))
+        elif msg_type == "set_process_filter":
+            self.proxy_service.set_process_filter(
+                [int(pid) for pid in msg.get("pids") or []],
+                [str(name) for name in msg.get("names") or []],
+            )
+        elif msg_type == "pause_on_error":
+            self.proxy_service.set_pause_on_error(
+                bool(msg.get("enabled", False)), int(msg.get("status_min") or 0)
+            )
+        elif msg_type == "force_http1":
+            self.proxy_service.set_force_http1([str(h) for h in msg.get("hosts") or []])
+        elif msg_type == "dedupe_by_endpoint":
+            self.proxy_service.set_dedupe_by_endpoint(str(msg.get("keep") or ""))
+        elif msg_type == "add_marker":
+            self.proxy_service.add_marker(str(msg.get("label", "")))
+        elif msg_type == "get_sidecar_info":
+            self.proxy_service.report_sidecar_info()
+        elif msg_type == "get_memory_usage":
+            self.proxy_service.report_memory_usage()
+        elif msg_type == "export_waterfall":
+            self.proxy_service.export_waterfall(msg.get("path", ""))
+        elif msg_type == "export_postman":
+            self.proxy_service.export_postman(msg.get("path", ""), msg.get("filter"))
+        elif msg_type == "compare_baseline":
+            self.proxy_service.compare_baseline(msg.get("baseline_path", ""))
+        elif msg_type == "start_tail":
+            self.proxy_service.start_tail(str(msg.get("host_pattern", "")), msg.get("path", ""))
+        elif msg_type == "stop_tail":
+            self.proxy_service.stop_tail()
+        elif msg_type == "set_proto_descriptor":
+            self.proxy_service.set_proto_descriptor(msg.get("descriptor_path", ""))
+        elif msg_type == "decode_proto":
+            self.proxy_service.decode_proto(msg.get("flow_id", ""))
+        elif msg_type == "get_hexdump":
+            self.proxy_service.get_hexdump(
+                msg.get("flow_id", ""),
+                str(msg.get("direction") or "response"),
+                int(msg.get("offset") or 0),
+                int(msg.get("length") or 0),
+            )
+        elif msg_type == "save_response_body":
+            self.proxy_service.save_response_body(msg.get("flow_id", ""), msg.get("path", ""))
+        elif msg_type == "export_openapi":
+            self.proxy_service.export_openapi(msg.get("path", ""), msg.get("filter"))
+        elif msg_type == "export_session_diff":
+            self.proxy_service.export_session_diff(msg.get("baseline_path", ""), msg.get("out_path", ""))
+        elif msg_type == "export_redacted_har":
+            self.proxy_service.export_redacted_har(
+                msg.get("path", ""),
+                msg.get("header_names") or [],
+                msg.get("body_patterns") or [],
+            )
+        elif msg_type == "start_http_bridge":
+            await self._start_http_bridge(int(msg.get("port", 0)))
+        elif msg_type == "stop_http_bridge":
+            await self.http_bridge.stop()
+            self.proxy_service.clear_degraded("http_bridge")
+            self._service_state("http_bridge", False)
+        elif msg_type == "start_metrics_endpoint":
+            await self._start_metrics_endpoint(int(msg.get("port", 0)))
+        elif msg_type == "stop_metrics_endpoint":
+            await self.metrics.stop()
+            self._service_state("metrics", False)
+        elif msg_type == "start_pac_server":
+            await self._start_pac_server(
+                int(msg.get("port", 0)),
+                _pac_host_patterns(msg.get("include")),
+                _pac_host_patterns(msg.get("exclude")),
+            )
+        elif msg_type == "stop_pac_server":
+            await self.pac_server.stop()
+            self._service_state("pac", False)
+        elif msg_type == "get_lan_addresses":
+            self.event_queue.put({"type": "lan_addresses", "addresses": _lan_addresses()})
+
+    async def _start_metrics_endpoint(self, port):
+        try:
+            await self.metrics.start(port)
+        except OSError as exc:
+            self.event_queue.put(
+                {"type": "error", "message": f"Metrics endpoint failed to start on {LOOPBACK_HOST}:{port}. {exc}"}
+            )
+            self._service_state("metrics", False)
+            return
+        self._service_state("metrics", True, f"http://{LOOPBACK_HOST}:{port}/metrics")
+
+    async def _start_pac_server(self, port, include=(), exclude=()):
+        # Serve on the proxy's interface so LAN devices can reach the PAC when bound to all.
+        self.pac_server.host = self.proxy_service.current_host
+        self.pac_server.include = list(include)
+        self.pac_server.exclude = list(exclude)
+        try:
+            await self.pac_server.start(port)
+        except OSError as exc:
+            self.event_queue.put(
+                {"type": "error", "message": f"PAC server failed to start on {self.pac_server.host}:{port}. {exc}"}
+            )
+            self._service_state("pac", False)
+            return
+        host = self.pac_server.host
+        if host == ALL_INTERFACES_HOST:
+            host = _lan_address()
+        self._service_state("pac", True, f"http://{host}:{port}/proxy.pac")
+
+    async def _start_http_bridge(self, port):
+        try:
+            await self.http_bridge.start(port)
+        except OSError as exc:
+            self.event_queue.put(
+                {"type": "error", "message": f"HTTP bridge failed to start on {LOOPBACK_HOST}:{port}. {exc}"}
+            )
+            self._service_state("http_bridge", False)
+            self.proxy_service.mark_degraded("http_bridge", f"HTTP bridge unavailable on port {port}")
+            return
+        self.proxy_service.clear_degraded("http_bridge")
+        self._service_state("http_bridge", True, f"http://{LOOPBACK_HOST}:{port}/events")
+
+    def _service_state(self, service, running, address=None):
+        self.event_queue.put({"type": "service_state", "service": service, "running": running, "address": address})
 
     async def broadcast(self, payload):
+        self.metrics.observe(payload)
+        await self.http_bridge.publish(payload)
         if not self.clients:
             return
         dead = []
@@ -449,6 +4179,8 @@ class IpcServer:
 async def pump_events(ipc_server, event_queue):
     while True:
         payload = await asyncio.to_thread(event_queue.get)
+        if payload.get("type") == "error":
+            ipc_server.proxy_service.record_error(payload.get("message", ""))
         await ipc_server.broadcast(payload)
 
 
@@ -0,0 +1,109 @@
+import os
+import queue
+import sys
+import types
+import unittest
+
+sys.path.insert(0, os.path.dirname(os.path.dirname(os.path.abspath(__file__))))
+
+import proxy_service  # noqa: E402
+
+STREAM_LIMIT = 16
+
+
+class RecordingClient:
+    """Stands in for ProxyClient and records what each replay sends."""
+
+    def __init__(self):
+        self.sent = []
+
+    def send(self, method, url, headers=None, body=b"", timeout=None, host_header=None, source_port=None):
+        self.sent.append((method, url, body))
+        return 200
+
+
+def _service():
+    service = proxy_service.ProxyService(queue.Queue())
+    service.client = RecordingClient()
+    service.state.stream_body_limit = STREAM_LIMIT
+    return service
+
+
+def _store_flow(service, body, stored=True):
+    """Stores a POST flow whose display body is truncated at the stream limit, like the record builders do."""
+    record = {
+        "id": "flow-1",
+        "method": "POST",
+        "url": "http://api.example.test/upload",
+        "started": 1.0,
+        "request_headers": [{"name": "Content-Type", "value": "application/json"}],
+        "request_body": body[:STREAM_LIMIT].decode(),
+        "request_body_truncated": True,
+        "request_body_size": len(body),
+    }
+    service.flow_store.add(record, body if stored else b"")
+    return record
+
+
+def _errors(service):
+    events = []
+    while not service.event_queue.empty():
+        events.append(service.event_queue.get_nowait())
+    return [event["message"] for event in events if event.get("type") == "error"]
+
+
+class TimedReplayTest(unittest.TestCase):
+    def test_sends_stored_body_longer_than_stream_limit(self):
+        service = _service()
+        body = b'{"payload": "' + b"a" * 64 + b'"}'
+        record = _store_flow(service, body)
+
+        service._run_timed_replay([record], 1.0)
+
+        self.assertEqual(service.client.sent, [("POST", record["url"], body)])
+
+    def test_reports_evicted_body_instead_of_sending_display_text(self):
+        service = _service()
+        record = _store_flow(service, b'{"payload": "' + b"a" * 64 + b'"}', stored=False)
+
+        service._run_timed_replay([record], 1.0)
+
+        self.assertEqual(service.client.sent, [])
+        self.assertTrue(any("no longer stored" in message for message in _errors(service)))
+
+
+class MultiReplayTest(unittest.TestCase):
+    def test_sends_stored_body_longer_than_stream_limit_to_every_target(self):
+        service = _service()
+        body = b'{"payload": "' + b"b" * 64 + b'"}'
+        record = _store_flow(service, body)
+
+        service._run_multi_replay(record, ["http://a.test", "http://b.test"], False)
+
+        self.assertEqual(
+            sorted(service.client.sent),
+            [("POST", "http://a.test/upload", body), ("POST", "http://b.test/upload", body)],
+        )
+
+
+class HandshakePinTest(unittest.TestCase):
+    def _connect(self, state, peername):
+        server = types.SimpleNamespace(address=("api.example.test", 443), sni="api.example.test", alpn_offers=[])
+        proxy_service.UpstreamTweaks(state).server_connect(
+            types.SimpleNamespace(server=server, client=types.SimpleNamespace(peername=peername))
+        )
+        return server
+
+    def test_pins_only_the_replay_connection(self):
+        state = proxy_service.CaptureState()
+        state.handshake_pins[("127.0.0.1", 50001)] = {"sni": "pinned.example.test", "alpn": "http/1.1"}
+
+        replay = self._connect(state, ("127.0.0.1", 50001))
+        live = self._connect(state, ("127.0.0.1", 50002))
+
+        self.assertEqual((replay.sni, replay.alpn_offers), ("pinned.example.test", [b"http/1.1"]))
+        self.assertEqual((live.sni, live.alpn_offers), ("api.example.test", []))
+
+
+if __name__ == "__main__":
+    unittest.main()
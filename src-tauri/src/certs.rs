@@ -0,0 +1,101 @@
+use std::net::IpAddr;
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::der_parser::oid::Oid;
+use x509_parser::extensions::GeneralName;
+use x509_parser::objects::{oid2sn, oid_registry};
+use x509_parser::pem::parse_x509_pem;
+
+use crate::flow_store::FlowStoreState;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub serial: String,
+    pub version: u32,
+    /// Validity bounds as Unix seconds.
+    pub not_before: i64,
+    pub not_after: i64,
+    pub signature_algorithm: String,
+    pub public_key_algorithm: String,
+    pub subject_alt_names: Vec<String>,
+    pub is_ca: bool,
+}
+
+fn oid_name(oid: &Oid) -> String {
+    oid2sn(oid, oid_registry())
+        .map(str::to_string)
+        .unwrap_or_else(|_| oid.to_id_string())
+}
+
+fn ip_text(bytes: &[u8]) -> String {
+    if let Ok(octets) = <[u8; 4]>::try_from(bytes) {
+        return IpAddr::from(octets).to_string();
+    }
+    if let Ok(octets) = <[u8; 16]>::try_from(bytes) {
+        return IpAddr::from(octets).to_string();
+    }
+    format!("{bytes:02x?}")
+}
+
+fn alt_names(cert: &X509Certificate) -> Vec<String> {
+    let Ok(Some(extension)) = cert.subject_alternative_name() else {
+        return Vec::new();
+    };
+    extension
+        .value
+        .general_names
+        .iter()
+        .map(|name| match name {
+            GeneralName::DNSName(dns) => format!("DNS:{dns}"),
+            GeneralName::IPAddress(bytes) => format!("IP:{}", ip_text(bytes)),
+            GeneralName::RFC822Name(email) => format!("email:{email}"),
+            GeneralName::URI(uri) => format!("URI:{uri}"),
+            other => format!("{other:?}"),
+        })
+        .collect()
+}
+
+fn certificate_info(index: usize, pem: &str) -> Result<CertificateInfo, String> {
+    let (_, pem) = parse_x509_pem(pem.as_bytes())
+        .map_err(|err| format!("Certificate {index} is not valid PEM: {err}"))?;
+    let cert = pem
+        .parse_x509()
+        .map_err(|err| format!("Certificate {index} could not be parsed: {err}"))?;
+    Ok(CertificateInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial: cert.raw_serial_as_string(),
+        version: cert.version().0 + 1,
+        not_before: cert.validity().not_before.timestamp(),
+        not_after: cert.validity().not_after.timestamp(),
+        signature_algorithm: oid_name(&cert.signature_algorithm.algorithm),
+        public_key_algorithm: oid_name(&cert.public_key().algorithm.algorithm),
+        subject_alt_names: alt_names(&cert),
+        is_ca: cert.is_ca(),
+    })
+}
+
+/// Parses the server certificate chain the sidecar recorded for an HTTPS flow, leaf first.
+#[tauri::command]
+pub fn get_certificate_chain(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+    flow_id: String,
+) -> Result<Vec<CertificateInfo>, String> {
+    let record = store
+        .record(&app, &flow_id)?
+        .ok_or_else(|| format!("Flow {flow_id} not found"))?;
+    if record.certificate_chain.is_empty() {
+        return Err(format!("Flow {flow_id} has no server certificate chain"));
+    }
+    record
+        .certificate_chain
+        .iter()
+        .enumerate()
+        .map(|(index, pem)| certificate_info(index, pem))
+        .collect()
+}
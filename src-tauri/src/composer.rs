@@ -0,0 +1,37 @@
+use tauri::State;
+
+use crate::ipc::{HeaderEntry, ProxyCommand};
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+/// Sends a hand-written request through the running proxy, or straight to the server when
+/// `direct` is set. Either way the exchange arrives as an ordinary `flow` event.
+#[tauri::command]
+pub fn send_request(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    method: String,
+    url: String,
+    headers: Vec<HeaderEntry>,
+    body: String,
+    direct: bool,
+) -> Result<(), String> {
+    let method = method.trim().to_ascii_uppercase();
+    if method.is_empty() || !method.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err("Method must be a single word such as GET or POST.".into());
+    }
+    let url = url.trim().to_string();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err("URL must start with http:// or https://.".into());
+    }
+    send_proxy_command(
+        state,
+        ipc_port,
+        ProxyCommand::SendRequest {
+            method,
+            url,
+            headers,
+            body,
+            direct,
+        },
+    )
+}
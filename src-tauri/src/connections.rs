@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::flow_store::FlowStoreState;
+
+/// Flows that went over one upstream connection, in capture order.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSummary {
+    pub server_conn_id: String,
+    pub host: String,
+    pub client_conn_ids: Vec<String>,
+    pub flow_ids: Vec<String>,
+    pub first_started: f64,
+    pub last_ended: f64,
+}
+
+/// Groups stored flows by upstream connection, oldest connection first. Flows that never
+/// reached a server (mocked, mapped to local files, failed before connecting) are left out.
+#[tauri::command]
+pub fn list_connections(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+) -> Result<Vec<ConnectionSummary>, String> {
+    let mut connections: Vec<ConnectionSummary> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for record in store.records(&app)? {
+        let Some(server_conn_id) = record.server_conn_id else {
+            continue;
+        };
+        let position = *index.entry(server_conn_id.clone()).or_insert_with(|| {
+            connections.push(ConnectionSummary {
+                server_conn_id,
+                host: record.host.clone(),
+                client_conn_ids: Vec::new(),
+                flow_ids: Vec::new(),
+                first_started: record.started,
+                last_ended: record.ended,
+            });
+            connections.len() - 1
+        });
+        let connection = &mut connections[position];
+        if let Some(client_conn_id) = record.client_conn_id {
+            if !connection.client_conn_ids.contains(&client_conn_id) {
+                connection.client_conn_ids.push(client_conn_id);
+            }
+        }
+        connection.flow_ids.push(record.id);
+        connection.last_ended = connection.last_ended.max(record.ended);
+    }
+    Ok(connections)
+}
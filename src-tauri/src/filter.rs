@@ -0,0 +1,382 @@
+use tauri::{AppHandle, State};
+
+use crate::flow_store::FlowStoreState;
+use crate::ipc::{FlowRecord, HeaderEntry};
+
+/// A parsed mitmproxy-style filter such as `~d example.com ~m POST !~c 200`.
+///
+/// Terms are ANDed when juxtaposed and combine with `&`, `|`, `!` and parentheses. Arguments
+/// match as case-insensitive substrings rather than regexes; a bare word is shorthand for `~u`.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Domain(String),
+    Method(String),
+    Code(i32),
+    Url(String),
+    Header(String),
+    RequestHeader(String),
+    ResponseHeader(String),
+    Body(String),
+    RequestBody(String),
+    ResponseBody(String),
+    ContentType(String),
+    Error,
+    NoResponse,
+    HasResponse,
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Not,
+    And,
+    Or,
+    Word(String),
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '!' | '&' | '|' => {
+                chars.next();
+                tokens.push(match ch {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    '!' => Token::Not,
+                    '&' => Token::And,
+                    _ => Token::Or,
+                });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == ch => break,
+                        Some('\\') => word.extend(chars.next()),
+                        Some(c) => word.push(c),
+                        None => return Err(format!("Unterminated quote in filter: {expr}")),
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()!&|".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> Result<Filter, String> {
+        let mut left = self.and_expr()?;
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            left = Filter::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Filter, String> {
+        let mut left = self.unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.advance();
+                }
+                Some(Token::Not | Token::Open | Token::Word(_)) => {}
+                _ => return Ok(left),
+            }
+            left = Filter::And(Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, String> {
+        match self.advance() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let inner = self.or_expr()?;
+                match self.advance() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err("Missing closing parenthesis in filter".into()),
+                }
+            }
+            Some(Token::Word(word)) => self.term(word),
+            Some(token) => Err(format!("Unexpected {token:?} in filter")),
+            None => Err("Filter ends unexpectedly".into()),
+        }
+    }
+
+    fn argument(&mut self, operator: &str) -> Result<String, String> {
+        match self.advance() {
+            Some(Token::Word(word)) => Ok(word.to_lowercase()),
+            _ => Err(format!("{operator} needs an argument")),
+        }
+    }
+
+    fn term(&mut self, word: String) -> Result<Filter, String> {
+        let Some(operator) = word.strip_prefix('~') else {
+            return Ok(Filter::Url(word.to_lowercase()));
+        };
+        let filter = match operator {
+            "d" => Filter::Domain(self.argument(&word)?),
+            "m" => Filter::Method(self.argument(&word)?),
+            "c" => {
+                let code = self.argument(&word)?;
+                Filter::Code(
+                    code.parse()
+                        .map_err(|_| format!("~c needs a status code, not {code}"))?,
+                )
+            }
+            "u" => Filter::Url(self.argument(&word)?),
+            "h" => Filter::Header(self.argument(&word)?),
+            "hq" => Filter::RequestHeader(self.argument(&word)?),
+            "hs" => Filter::ResponseHeader(self.argument(&word)?),
+            "b" => Filter::Body(self.argument(&word)?),
+            "bq" => Filter::RequestBody(self.argument(&word)?),
+            "bs" => Filter::ResponseBody(self.argument(&word)?),
+            "t" => Filter::ContentType(self.argument(&word)?),
+            "e" => Filter::Error,
+            "q" => Filter::NoResponse,
+            "s" => Filter::HasResponse,
+            _ => return Err(format!("Unknown filter operator {word}")),
+        };
+        Ok(filter)
+    }
+}
+
+fn headers_contain(headers: &[HeaderEntry], needle: &str) -> bool {
+    headers.iter().any(|h| {
+        format!("{}: {}", h.name, h.value)
+            .to_lowercase()
+            .contains(needle)
+    })
+}
+
+fn content_type(headers: &[HeaderEntry]) -> String {
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case("content-type"))
+        .map(|h| h.value.to_lowercase())
+        .unwrap_or_default()
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(expr)?,
+            pos: 0,
+        };
+        let filter = parser.or_expr()?;
+        match parser.advance() {
+            None => Ok(filter),
+            Some(token) => Err(format!("Unexpected {token:?} in filter")),
+        }
+    }
+
+    pub fn matches(&self, record: &FlowRecord) -> bool {
+        let response_headers = record.response_headers.as_deref().unwrap_or_default();
+        match self {
+            Filter::Domain(domain) => record.host.to_lowercase().contains(domain),
+            Filter::Method(method) => record.method.eq_ignore_ascii_case(method),
+            Filter::Code(code) => record.status_code == *code,
+            Filter::Url(url) => record.url.to_lowercase().contains(url),
+            Filter::Header(needle) => {
+                headers_contain(&record.request_headers, needle)
+                    || headers_contain(response_headers, needle)
+            }
+            Filter::RequestHeader(needle) => headers_contain(&record.request_headers, needle),
+            Filter::ResponseHeader(needle) => headers_contain(response_headers, needle),
+            Filter::Body(needle) => {
+                record.request_body.to_lowercase().contains(needle)
+                    || record.response_body.to_lowercase().contains(needle)
+            }
+            Filter::RequestBody(needle) => record.request_body.to_lowercase().contains(needle),
+            Filter::ResponseBody(needle) => record.response_body.to_lowercase().contains(needle),
+            Filter::ContentType(needle) => {
+                content_type(&record.request_headers).contains(needle)
+                    || content_type(response_headers).contains(needle)
+            }
+            Filter::Error => !record.error.is_empty(),
+            Filter::NoResponse => record.response_headers.is_none(),
+            Filter::HasResponse => record.response_headers.is_some(),
+            Filter::Not(inner) => !inner.matches(record),
+            Filter::And(left, right) => left.matches(record) && right.matches(record),
+            Filter::Or(left, right) => left.matches(record) || right.matches(record),
+        }
+    }
+}
+
+/// Returns the ids of stored flows matching `expr`, in capture order.
+#[tauri::command]
+pub fn filter_flows(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+    expr: String,
+) -> Result<Vec<String>, String> {
+    let filter = Filter::parse(&expr)?;
+    Ok(store
+        .records(&app)?
+        .into_iter()
+        .filter(|record| filter.matches(record))
+        .map(|record| record.id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn record() -> FlowRecord {
+        serde_json::from_value(json!({
+            "id": "flow-1",
+            "started": 0.0,
+            "ended": 0.0,
+            "duration_ms": 0,
+            "method": "POST",
+            "url": "https://api.example.com/v1/users?page=2",
+            "host": "api.example.com",
+            "path": "/v1/users?page=2",
+            "scheme": "https",
+            "status_code": 201,
+            "request_headers": [
+                {"name": "Content-Type", "value": "application/json"},
+                {"name": "Authorization", "value": "Bearer token"}
+            ],
+            "response_headers": [
+                {"name": "Content-Type", "value": "text/html; charset=utf-8"},
+                {"name": "X-Trace", "value": "abc123"}
+            ],
+            "request_body_size": 16,
+            "response_body_size": 12,
+            "request_body": "{\"name\":\"Ada\"}",
+            "response_body": "<p>Created</p>",
+            "request_body_truncated": false,
+            "response_body_truncated": false,
+            "error": ""
+        }))
+        .unwrap()
+    }
+
+    fn matches(expr: &str, record: &FlowRecord) -> bool {
+        Filter::parse(expr).unwrap().matches(record)
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expr in [
+            "",
+            "~d",
+            "(~m POST",
+            "~m POST)",
+            "~x foo",
+            "~c abc",
+            "\"open",
+            "~m POST |",
+        ] {
+            assert!(Filter::parse(expr).is_err(), "{expr} should not parse");
+        }
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and_and_and_tighter_than_or() {
+        let record = record();
+        // !~m GET & ~c 404 | ~d example  ==  ((!~m GET) & ~c 404) | ~d example
+        assert!(matches("!~m GET & ~c 404 | ~d example", &record));
+        assert!(!matches("!~m POST & ~c 201 | ~d other", &record));
+        assert!(!matches("~d other | ~m POST & ~c 404", &record));
+        assert!(matches("(~d other | ~m POST) & ~c 201", &record));
+        assert!(!matches("!(~m POST ~c 201)", &record));
+        assert!(matches("!!~m post", &record));
+    }
+
+    #[test]
+    fn juxtaposed_terms_are_anded() {
+        let record = record();
+        assert!(matches("~d example.com ~m POST", &record));
+        assert!(!matches("~d example.com ~m GET", &record));
+    }
+
+    #[test]
+    fn each_operator_matches_its_field() {
+        let record = record();
+        let cases = [
+            ("~d API.Example", true),
+            ("~d other.org", false),
+            ("~m post", true),
+            ("~m pos", false),
+            ("~c 201", true),
+            ("~c 200", false),
+            ("~u /v1/users", true),
+            ("users", true),
+            ("~u /v2", false),
+            ("~h authorization", true),
+            ("~h x-trace", true),
+            ("~hq x-trace", false),
+            ("~hq \"bearer token\"", true),
+            ("~hs abc123", true),
+            ("~hs authorization", false),
+            ("~b ada", true),
+            ("~b created", true),
+            ("~bq created", false),
+            ("~bq ada", true),
+            ("~bs created", true),
+            ("~bs ada", false),
+            ("~t json", true),
+            ("~t html", true),
+            ("~t xml", false),
+            ("~e", false),
+            ("~q", false),
+            ("~s", true),
+        ];
+        for (expr, expected) in cases {
+            assert_eq!(matches(expr, &record), expected, "{expr}");
+        }
+    }
+
+    #[test]
+    fn error_and_response_state() {
+        let mut record = record();
+        record.error = "connection reset".into();
+        record.response_headers = None;
+        assert!(matches("~e", &record));
+        assert!(matches("~q", &record));
+        assert!(!matches("~s", &record));
+        assert!(!matches("~t html", &record));
+    }
+}
@@ -0,0 +1,468 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use tauri::{AppHandle, Manager, State};
+
+use crate::filter::Filter;
+use crate::ipc::FlowRecord;
+
+const DATABASE_FILE: &str = "flows.sqlite3";
+const MAX_PAGE_SIZE: u32 = 1000;
+/// Unpinned flows beyond this count, oldest first, are pruned.
+const MAX_STORED_FLOWS: u32 = 50_000;
+/// Unpinned flows that started longer ago than this are pruned.
+const MAX_FLOW_AGE_SECS: f64 = 24.0 * 60.0 * 60.0;
+/// The caps are enforced once per this many inserts rather than after every one.
+const PRUNE_INTERVAL: u32 = 256;
+
+/// Every flow the listener receives, kept on disk so long sessions can be paged instead of
+/// held in the webview. The table is emptied the first time it is opened in each app run,
+/// follows the sidecar's removals, and is capped by count and age; pinned flows always stay.
+#[derive(Default)]
+pub struct FlowStoreState {
+    conn: Mutex<Option<Connection>>,
+    inserts_since_prune: AtomicU32,
+}
+
+impl FlowStoreState {
+    pub(crate) fn with_conn<T>(
+        &self,
+        app: &AppHandle,
+        query: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut guard = self.conn.lock().map_err(|_| "Flow store lock poisoned")?;
+        if guard.is_none() {
+            *guard = Some(open_database(app)?);
+        }
+        let conn = guard.as_ref().ok_or("Flow store is unavailable")?;
+        query(conn).map_err(|err| format!("Flow store error: {err}"))
+    }
+
+    pub fn insert(&self, app: &AppHandle, record: &FlowRecord) -> Result<(), String> {
+        self.with_conn(app, |conn| insert_record(conn, record))?;
+        self.inserts_since_prune.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Deletes a flow the sidecar evicted or cleared, unless it is pinned.
+    pub fn remove(&self, app: &AppHandle, id: &str) -> Result<(), String> {
+        self.with_conn(app, |conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute(
+                "DELETE FROM flows_fts
+                 WHERE rowid = (SELECT rowid FROM flows WHERE id = ?1 AND pinned = 0)",
+                params![id],
+            )?;
+            tx.execute(
+                "DELETE FROM flows WHERE id = ?1 AND pinned = 0",
+                params![id],
+            )?;
+            tx.commit()
+        })
+    }
+
+    pub fn set_pinned(&self, app: &AppHandle, id: &str, pinned: bool) -> Result<(), String> {
+        self.with_conn(app, |conn| {
+            conn.execute(
+                "UPDATE flows SET pinned = ?2 WHERE id = ?1",
+                params![id, pinned],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// Drops unpinned flows past the count or age cap, once every `PRUNE_INTERVAL` inserts,
+    /// and returns their ids.
+    pub fn prune(&self, app: &AppHandle) -> Result<Vec<String>, String> {
+        if self.inserts_since_prune.load(Ordering::Relaxed) < PRUNE_INTERVAL {
+            return Ok(Vec::new());
+        }
+        self.inserts_since_prune.store(0, Ordering::Relaxed);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+        self.with_conn(app, |conn| prune_flows(conn, now))
+    }
+
+    pub fn record(&self, app: &AppHandle, id: &str) -> Result<Option<FlowRecord>, String> {
+        self.with_conn(app, |conn| {
+            conn.query_row(
+                "SELECT record, pinned FROM flows WHERE id = ?1",
+                params![id],
+                read_record,
+            )
+            .optional()
+        })
+    }
+
+    pub fn records(&self, app: &AppHandle) -> Result<Vec<FlowRecord>, String> {
+        self.with_conn(app, |conn| {
+            let mut statement =
+                conn.prepare("SELECT record, pinned FROM flows ORDER BY started, rowid")?;
+            let rows = statement.query_map([], read_record)?;
+            rows.collect()
+        })
+    }
+}
+
+pub(crate) fn insert_record(conn: &Connection, record: &FlowRecord) -> rusqlite::Result<()> {
+    let json = serde_json::to_string(record)
+        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM flows_fts WHERE rowid = (SELECT rowid FROM flows WHERE id = ?1)",
+        params![record.id],
+    )?;
+    tx.execute(
+        "INSERT OR REPLACE INTO flows
+             (id, started, method, host, url, status_code, pinned, record)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            record.id,
+            record.started,
+            record.method,
+            record.host,
+            record.url,
+            record.status_code,
+            record.pinned,
+            json
+        ],
+    )?;
+    tx.execute(
+        "INSERT INTO flows_fts (rowid, url, headers, request_body, response_body)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            tx.last_insert_rowid(),
+            record.url,
+            headers_text(record),
+            record.request_body,
+            record.response_body
+        ],
+    )?;
+    tx.commit()
+}
+
+/// Deletes unpinned flows older than the age cap, then the oldest ones past the count cap.
+/// Both passes walk the `(pinned, started)` index.
+fn prune_flows(conn: &Connection, now: f64) -> rusqlite::Result<Vec<String>> {
+    let tx = conn.unchecked_transaction()?;
+    let mut pruned: Vec<(i64, String)> = {
+        let mut statement =
+            tx.prepare("SELECT rowid, id FROM flows WHERE pinned = 0 AND started < ?1")?;
+        let rows = statement.query_map(params![now - MAX_FLOW_AGE_SECS], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let unpinned: u32 = tx.query_row(
+        "SELECT COUNT(*) FROM flows WHERE pinned = 0 AND started >= ?1",
+        params![now - MAX_FLOW_AGE_SECS],
+        |row| row.get(0),
+    )?;
+    if unpinned > MAX_STORED_FLOWS {
+        let mut statement = tx.prepare(
+            "SELECT rowid, id FROM flows WHERE pinned = 0 AND started >= ?1
+             ORDER BY started, rowid LIMIT ?2",
+        )?;
+        let rows = statement.query_map(
+            params![now - MAX_FLOW_AGE_SECS, unpinned - MAX_STORED_FLOWS],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        for row in rows {
+            pruned.push(row?);
+        }
+    }
+    for (rowid, _) in &pruned {
+        tx.execute("DELETE FROM flows_fts WHERE rowid = ?1", params![rowid])?;
+        tx.execute("DELETE FROM flows WHERE rowid = ?1", params![rowid])?;
+    }
+    tx.commit()?;
+    Ok(pruned.into_iter().map(|(_, id)| id).collect())
+}
+
+fn open_database(app: &AppHandle) -> Result<Connection, String> {
+    let dir: PathBuf = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Unable to resolve app data folder: {err}"))?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    let path = dir.join(DATABASE_FILE);
+    let conn = Connection::open(&path)
+        .map_err(|err| format!("Unable to open {}: {err}", path.display()))?;
+    conn.execute_batch("PRAGMA journal_mode = WAL;")
+        .and_then(|()| prepare_schema(&conn))
+        .map_err(|err| format!("Unable to prepare {}: {err}", path.display()))?;
+    Ok(conn)
+}
+
+/// Recreates the tables for a new run, so a database left by an older build never needs
+/// migrating. `flows_fts` rows share the rowid of their `flows` row.
+pub(crate) fn prepare_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "DROP TABLE IF EXISTS flows;
+         DROP TABLE IF EXISTS flows_fts;
+         CREATE TABLE flows (
+             id TEXT PRIMARY KEY,
+             started REAL NOT NULL,
+             method TEXT NOT NULL,
+             host TEXT NOT NULL,
+             url TEXT NOT NULL,
+             status_code INTEGER NOT NULL,
+             pinned INTEGER NOT NULL DEFAULT 0,
+             record TEXT NOT NULL
+         );
+         CREATE INDEX flows_started ON flows (started);
+         CREATE INDEX flows_pinned_started ON flows (pinned, started);
+         CREATE INDEX flows_method ON flows (method COLLATE NOCASE);
+         CREATE INDEX flows_status_code ON flows (status_code);
+         CREATE VIRTUAL TABLE flows_fts USING fts5 (url, headers, request_body, response_body);",
+    )
+}
+
+/// Request and response headers as `name: value` lines, the form indexed for search.
+pub(crate) fn headers_text(record: &FlowRecord) -> String {
+    record
+        .request_headers
+        .iter()
+        .chain(record.response_headers.iter().flatten())
+        .map(|h| format!("{}: {}\n", h.name, h.value))
+        .collect()
+}
+
+/// Reads a row selected as `record, pinned`; the column, not the JSON, says whether it is pinned.
+pub(crate) fn read_record(row: &Row) -> rusqlite::Result<FlowRecord> {
+    let json: String = row.get(0)?;
+    let mut record: FlowRecord = serde_json::from_str(&json).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err))
+    })?;
+    record.pinned = row.get(1)?;
+    Ok(record)
+}
+
+/// Adds the `~d`, `~m` and `~c` terms that every match must satisfy as conditions on the flow
+/// columns, so SQL skips most non-matching rows before their records are parsed.
+fn column_conditions(filter: &Filter, conditions: &mut Vec<(&'static str, Value)>) {
+    match filter {
+        Filter::And(left, right) => {
+            column_conditions(left, conditions);
+            column_conditions(right, conditions);
+        }
+        // SQLite's lower() only folds ASCII, so other needles are left to Filter::matches.
+        Filter::Domain(domain) if domain.is_ascii() => {
+            conditions.push(("instr(lower(host), ?) > 0", Value::Text(domain.clone())));
+        }
+        Filter::Method(method) => {
+            conditions.push(("method = ? COLLATE NOCASE", Value::Text(method.clone())));
+        }
+        Filter::Code(code) => {
+            conditions.push(("status_code = ?", Value::Integer((*code).into())));
+        }
+        _ => {}
+    }
+}
+
+/// Streams flows in capture order and stops once a page of matches has been collected.
+fn filtered_page(
+    conn: &Connection,
+    filter: &Filter,
+    offset: u32,
+    limit: u32,
+) -> rusqlite::Result<Vec<FlowRecord>> {
+    let mut conditions = Vec::new();
+    column_conditions(filter, &mut conditions);
+    let (clauses, values): (Vec<_>, Vec<_>) = conditions.into_iter().unzip();
+    let where_clause = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    let mut statement = conn.prepare(&format!(
+        "SELECT record, pinned FROM flows {where_clause} ORDER BY started, rowid"
+    ))?;
+    let mut rows = statement.query(params_from_iter(values))?;
+    let mut records = Vec::new();
+    let mut skipped = 0;
+    while records.len() < limit as usize {
+        let Some(row) = rows.next()? else {
+            break;
+        };
+        let record = read_record(row)?;
+        if !filter.matches(&record) {
+            continue;
+        }
+        if skipped < offset {
+            skipped += 1;
+        } else {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+/// Returns a page of flows in capture order; `filter` uses the same expression syntax as
+/// `filter_flows`, and `offset` counts matching flows only.
+#[tauri::command]
+pub fn query_flows(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+    offset: u32,
+    limit: u32,
+    filter: Option<String>,
+) -> Result<Vec<FlowRecord>, String> {
+    let limit = limit.min(MAX_PAGE_SIZE);
+    let filter = filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|expr| !expr.is_empty())
+        .map(Filter::parse)
+        .transpose()?;
+    if let Some(filter) = filter {
+        return store.with_conn(&app, |conn| filtered_page(conn, &filter, offset, limit));
+    }
+    store.with_conn(&app, |conn| {
+        let mut statement = conn.prepare(
+            "SELECT record, pinned FROM flows
+             ORDER BY started, rowid
+             LIMIT ?1 OFFSET ?2",
+        )?;
+        let rows = statement.query_map(params![limit, offset], read_record)?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn get_flow(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+    id: String,
+) -> Result<Option<FlowRecord>, String> {
+    store.record(&app, &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn record(id: &str, started: f64, method: &str, host: &str, status_code: i32) -> FlowRecord {
+        serde_json::from_value(json!({
+            "id": id,
+            "started": started,
+            "ended": started,
+            "duration_ms": 0,
+            "method": method,
+            "url": format!("https://{host}/"),
+            "host": host,
+            "path": "/",
+            "scheme": "https",
+            "status_code": status_code,
+            "request_headers": [],
+            "response_headers": [],
+            "request_body_size": 0,
+            "response_body_size": 0,
+            "request_body": "",
+            "response_body": "",
+            "request_body_truncated": false,
+            "response_body_truncated": false,
+            "error": ""
+        }))
+        .unwrap()
+    }
+
+    fn database(records: &[FlowRecord]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        prepare_schema(&conn).unwrap();
+        for record in records {
+            insert_record(&conn, record).unwrap();
+        }
+        conn
+    }
+
+    fn page(conn: &Connection, expr: &str, offset: u32, limit: u32) -> Vec<String> {
+        filtered_page(conn, &Filter::parse(expr).unwrap(), offset, limit)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.id)
+            .collect()
+    }
+
+    #[test]
+    fn pushes_conjunct_terms_down_to_columns() {
+        let mut conditions = Vec::new();
+        column_conditions(
+            &Filter::parse("~d Example ~m post ~c 404 (~u a | ~u b)").unwrap(),
+            &mut conditions,
+        );
+        assert_eq!(
+            conditions,
+            vec![
+                ("instr(lower(host), ?) > 0", Value::Text("example".into())),
+                ("method = ? COLLATE NOCASE", Value::Text("post".into())),
+                ("status_code = ?", Value::Integer(404)),
+            ]
+        );
+
+        let mut conditions = Vec::new();
+        column_conditions(&Filter::parse("~m GET | ~c 200").unwrap(), &mut conditions);
+        column_conditions(&Filter::parse("!~d example").unwrap(), &mut conditions);
+        assert!(conditions.is_empty());
+    }
+
+    #[test]
+    fn pages_over_matching_flows_in_capture_order() {
+        let conn = database(&[
+            record("a", 1.0, "GET", "api.example.com", 200),
+            record("b", 2.0, "POST", "API.EXAMPLE.COM", 201),
+            record("c", 3.0, "post", "cdn.other.net", 201),
+            record("d", 4.0, "POST", "www.example.com", 500),
+            record("e", 5.0, "POST", "example.com", 201),
+        ]);
+        assert_eq!(page(&conn, "~m POST ~d example", 0, 10), ["b", "d", "e"]);
+        assert_eq!(page(&conn, "~m POST ~d example", 1, 1), ["d"]);
+        assert!(page(&conn, "~m POST ~d example", 3, 10).is_empty());
+        assert_eq!(page(&conn, "~c 201 !~d other", 0, 10), ["b", "e"]);
+        assert_eq!(page(&conn, "~m get | ~c 500", 0, 10), ["a", "d"]);
+        assert!(page(&conn, "~d example", 0, 0).is_empty());
+    }
+
+    #[test]
+    fn prunes_old_unpinned_flows_and_reads_pinning_from_the_column() {
+        let mut pinned = record("pinned", 1.0, "GET", "example.com", 200);
+        pinned.pinned = true;
+        let conn = database(&[
+            record("old", 1.0, "GET", "example.com", 200),
+            pinned,
+            record("new", 2.0 * MAX_FLOW_AGE_SECS, "GET", "example.com", 200),
+        ]);
+        conn.execute("UPDATE flows SET pinned = 1 WHERE id = 'new'", [])
+            .unwrap();
+
+        assert_eq!(
+            prune_flows(&conn, 1.5 * MAX_FLOW_AGE_SECS).unwrap(),
+            ["old"]
+        );
+        let remaining: Vec<(String, bool)> = filtered_page(&conn, &Filter::HasResponse, 0, 10)
+            .unwrap()
+            .into_iter()
+            .map(|record| (record.id, record.pinned))
+            .collect();
+        assert_eq!(
+            remaining,
+            [("pinned".to_string(), true), ("new".to_string(), true)]
+        );
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM flows_fts WHERE flows_fts MATCH 'example'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 2);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+use crate::flow_store::FlowStoreState;
+use crate::ipc::FlowRecord;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphqlOperation {
+    pub operation_type: String,
+    pub name: Option<String>,
+    pub count: u64,
+}
+
+/// Operations defined in a GraphQL document as (type, name), in document order. Fragments
+/// are skipped and a bare `{ ... }` selection counts as an anonymous query.
+fn operations(document: &str) -> Vec<(String, Option<String>)> {
+    let mut found = Vec::new();
+    let mut pending: Option<(String, Option<String>)> = None;
+    let (mut depth, mut parens) = (0usize, 0usize);
+    let (mut in_fragment, mut skip_word) = (false, false);
+    let mut chars = document.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        match ch {
+            '#' => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                let mut escaped = false;
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '(' => parens += 1,
+            ')' => parens = parens.saturating_sub(1),
+            '@' | '$' => skip_word = true,
+            '{' => {
+                if depth == 0 && parens == 0 {
+                    match pending.take() {
+                        Some(operation) => found.push(operation),
+                        None if !in_fragment => found.push(("query".to_string(), None)),
+                        None => {}
+                    }
+                }
+                depth += 1;
+            }
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    in_fragment = false;
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(index, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = index + c.len_utf8();
+                    chars.next();
+                }
+                if std::mem::take(&mut skip_word) || depth > 0 || parens > 0 || in_fragment {
+                    continue;
+                }
+                let word = &document[start..end];
+                match (&mut pending, word) {
+                    (None, "query" | "mutation" | "subscription") => {
+                        pending = Some((word.to_string(), None));
+                    }
+                    (None, "fragment") => in_fragment = true,
+                    (Some((_, name @ None)), _) => *name = Some(word.to_string()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Fills the GraphQL fields of `record` when its request body is a GraphQL operation, either
+/// as JSON (`{"query", "operationName", "variables"}`, or a batch of them) or as a raw
+/// `application/graphql` document.
+pub fn enrich(record: &mut FlowRecord) {
+    if !record.method.eq_ignore_ascii_case("POST") || record.request_body.is_empty() {
+        return;
+    }
+    let (document, operation_name, variables) =
+        match serde_json::from_str::<Value>(&record.request_body) {
+            Ok(Value::Array(mut batch)) if !batch.is_empty() => split_request(batch.swap_remove(0)),
+            Ok(body @ Value::Object(_)) => split_request(body),
+            Ok(_) => return,
+            Err(_) => {
+                let is_graphql = record.request_headers.iter().any(|h| {
+                    h.name.eq_ignore_ascii_case("content-type")
+                        && h.value
+                            .to_ascii_lowercase()
+                            .starts_with("application/graphql")
+                });
+                if !is_graphql {
+                    return;
+                }
+                (Some(record.request_body.clone()), None, None)
+            }
+        };
+    let Some(document) = document else {
+        return;
+    };
+    let defined = operations(&document);
+    let chosen = match &operation_name {
+        Some(wanted) => defined
+            .iter()
+            .find(|(_, name)| name.as_deref() == Some(wanted.as_str())),
+        None => defined.first(),
+    };
+    let Some((operation_type, name)) = chosen else {
+        return;
+    };
+    record.graphql_operation_type = Some(operation_type.clone());
+    record.graphql_operation_name = operation_name.or_else(|| name.clone());
+    record.graphql_variables = variables;
+}
+
+/// (query, operationName, variables) from one JSON GraphQL request.
+fn split_request(mut body: Value) -> (Option<String>, Option<String>, Option<Value>) {
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+    let variables = body
+        .get_mut("variables")
+        .map(Value::take)
+        .filter(|vars| !vars.is_null());
+    (
+        text(body.get("query")),
+        text(body.get("operationName")),
+        variables,
+    )
+}
+
+/// Distinct GraphQL operations among stored flows, most frequent first.
+#[tauri::command]
+pub fn list_graphql_operations(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+) -> Result<Vec<GraphqlOperation>, String> {
+    let mut counts: BTreeMap<(String, Option<String>), u64> = BTreeMap::new();
+    for record in store.records(&app)? {
+        if let Some(operation_type) = record.graphql_operation_type {
+            *counts
+                .entry((operation_type, record.graphql_operation_name))
+                .or_default() += 1;
+        }
+    }
+    let mut operations: Vec<GraphqlOperation> = counts
+        .into_iter()
+        .map(|((operation_type, name), count)| GraphqlOperation {
+            operation_type,
+            name,
+            count,
+        })
+        .collect();
+    operations.sort_by(|a, b| b.count.cmp(&a.count));
+    Ok(operations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(operation_type: &str, name: Option<&str>) -> (String, Option<String>) {
+        (operation_type.to_string(), name.map(str::to_string))
+    }
+
+    #[test]
+    fn bare_selection_is_an_anonymous_query() {
+        assert_eq!(operations("{ viewer { login } }"), vec![op("query", None)]);
+    }
+
+    #[test]
+    fn lists_named_operations_in_document_order() {
+        let document = "
+            query Viewer($first: Int = 10) @cached { viewer { repos(first: $first) { name } } }
+            mutation AddStar { addStar(input: {id: \"1\"}) { clientMutationId } }
+            subscription { events { id } }
+        ";
+        assert_eq!(
+            operations(document),
+            vec![
+                op("query", Some("Viewer")),
+                op("mutation", Some("AddStar")),
+                op("subscription", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_fragments_comments_and_strings() {
+        let document = r#"
+            # query Commented { a }
+            fragment Fields on User { name query }
+            query Find { user(note: "mutation Fake { x }") { ...Fields } }
+        "#;
+        assert_eq!(operations(document), vec![op("query", Some("Find"))]);
+    }
+
+    #[test]
+    fn field_names_inside_selections_are_not_operations() {
+        assert_eq!(
+            operations("query Outer { mutation { query } subscription }"),
+            vec![op("query", Some("Outer"))]
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use tauri::State;
+
+use crate::ipc::ProxyCommand;
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+/// Loads compiled `.proto` descriptor sets (`protoc --descriptor_set_out`) into the sidecar's
+/// descriptor pool, alongside any loaded before. gRPC flows captured afterwards carry their
+/// messages decoded to JSON in `FlowRecord::grpc`; without descriptors only the frames are listed.
+#[tauri::command]
+pub fn register_proto_descriptors(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("Choose at least one descriptor set.".into());
+    }
+    for descriptor_path in paths {
+        send_proxy_command(
+            state.clone(),
+            ipc_port,
+            ProxyCommand::SetProtoDescriptor { descriptor_path },
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::fs;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::ipc::{FlowRecord, HeaderEntry};
+
+#[derive(Deserialize)]
+struct HarFile {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarEntry {
+    started_date_time: String,
+    #[serde(default)]
+    time: f64,
+    request: HarRequest,
+    #[serde(default)]
+    response: Option<HarResponse>,
+    #[serde(default, rename = "_error")]
+    error: Option<String>,
+    #[serde(default, rename = "_clientOutcome")]
+    client_outcome: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    http_version: Option<String>,
+    #[serde(default)]
+    headers: Vec<HeaderEntry>,
+    #[serde(default)]
+    body_size: i64,
+    #[serde(default)]
+    post_data: Option<HarBody>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    #[serde(default)]
+    status: i32,
+    #[serde(default)]
+    headers: Vec<HeaderEntry>,
+    #[serde(default)]
+    body_size: i64,
+    #[serde(default)]
+    content: Option<HarContent>,
+}
+
+#[derive(Deserialize)]
+struct HarBody {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct HarContent {
+    #[serde(default)]
+    size: i64,
+    #[serde(default)]
+    text: String,
+}
+
+/// Splits an absolute URL into (scheme, host, path with query).
+fn split_url(url: &str) -> (String, String, String) {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = if host.starts_with('[') {
+        host.split_once(']')
+            .map_or(host, |(ip, _)| ip.trim_start_matches('['))
+    } else {
+        host.split(':').next().unwrap_or(host)
+    };
+    let path = if path.starts_with('?') {
+        format!("/{path}")
+    } else {
+        path.to_string()
+    };
+    (scheme.to_lowercase(), host.to_string(), path)
+}
+
+/// Parses an RFC 3339 timestamp such as `2024-05-01T12:30:00.250+02:00` into Unix seconds.
+fn parse_timestamp(text: &str) -> Option<f64> {
+    let (date, time) = text.trim().split_once(['T', ' '])?;
+    let mut date_parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_parts.next()??,
+        date_parts.next()??,
+        date_parts.next()??,
+    );
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+        let (hours, minutes) = time[index + 1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        (
+            &time[..index],
+            if time[index..].starts_with('-') {
+                -offset
+            } else {
+                offset
+            },
+        )
+    } else {
+        (time, 0)
+    };
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour = clock_parts.next()?.parse::<i64>().ok()?;
+    let minute = clock_parts.next()?.parse::<i64>().ok()?;
+    let second = clock_parts.next().unwrap_or("0").parse::<f64>().ok()?;
+
+    // Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's days_from_civil).
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some((days * 86_400 + hour * 3600 + minute * 60 - offset_secs) as f64 + second)
+}
+
+fn entry_to_record(index: usize, entry: HarEntry) -> Result<FlowRecord, String> {
+    let started = parse_timestamp(&entry.started_date_time)
+        .ok_or_else(|| format!("Entry {index} has an invalid startedDateTime"))?;
+    let (scheme, host, path) = split_url(&entry.request.url);
+    let response = entry.response;
+    let content = response.as_ref().and_then(|r| r.content.as_ref());
+    let response_body_size = response.as_ref().map_or(0, |r| r.body_size.max(0));
+    let record = json!({
+        "id": format!("har-{index}-{}", entry.started_date_time),
+        "started": started,
+        "ended": started + entry.time.max(0.0) / 1000.0,
+        "duration_ms": entry.time.max(0.0) as i64,
+        "method": entry.request.method,
+        "url": entry.request.url,
+        "host": host,
+        "path": path,
+        "scheme": scheme,
+        "http_version": entry.request.http_version,
+        "status_code": response.as_ref().map_or(0, |r| r.status),
+        "request_headers": entry.request.headers,
+        "response_headers": response.as_ref().map(|r| &r.headers),
+        "request_body_size": entry.request.body_size.max(0),
+        "response_body_size": response_body_size,
+        "response_body_decoded_size": content
+            .map(|c| c.size)
+            .filter(|size| *size > response_body_size),
+        "request_body": entry.request.post_data.map(|body| body.text).unwrap_or_default(),
+        "response_body": content.map(|c| c.text.clone()).unwrap_or_default(),
+        "request_body_truncated": false,
+        "response_body_truncated": false,
+        "error": entry.error.unwrap_or_default(),
+        "client_outcome": entry.client_outcome,
+    });
+    serde_json::from_value(record)
+        .map_err(|err| format!("Entry {index} is not a valid flow: {err}"))
+}
+
+/// Loads a HAR file as flow records for offline viewing; the sidecar is not involved.
+#[tauri::command]
+pub fn import_har(path: String) -> Result<Vec<FlowRecord>, String> {
+    let text =
+        fs::read_to_string(path.trim()).map_err(|err| format!("Unable to read {path}: {err}"))?;
+    let har: HarFile = serde_json::from_str(&text)
+        .map_err(|err| format!("{path} is not a valid HAR file: {err}"))?;
+    har.log
+        .entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| entry_to_record(index, entry))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(scheme: &str, host: &str, path: &str) -> (String, String, String) {
+        (scheme.to_string(), host.to_string(), path.to_string())
+    }
+
+    #[test]
+    fn splits_urls_into_scheme_host_and_path() {
+        assert_eq!(
+            split_url("https://user:pw@Example.com:8443/a/b?c=1"),
+            parts("https", "Example.com", "/a/b?c=1")
+        );
+        assert_eq!(
+            split_url("HTTP://example.com"),
+            parts("http", "example.com", "/")
+        );
+        assert_eq!(split_url("http://[::1]:8080/x"), parts("http", "::1", "/x"));
+        assert_eq!(
+            split_url("example.com?q=1"),
+            parts("http", "example.com", "/?q=1")
+        );
+    }
+
+    #[test]
+    fn parses_rfc3339_timestamps() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_timestamp("1969-12-31T23:59:59Z"), Some(-1.0));
+        assert_eq!(
+            parse_timestamp("2024-05-01T12:30:00.250+02:00"),
+            Some(1_714_559_400.25)
+        );
+        assert_eq!(
+            parse_timestamp("2000-02-29 00:00:00-05:30"),
+            Some(951_802_200.0)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps() {
+        for text in [
+            "",
+            "2024-05-01",
+            "2024-05-01Tnoon",
+            "2024-05T12:00:00Z",
+            "2024-05-01T12:00:00+0200",
+        ] {
+            assert_eq!(parse_timestamp(text), None, "{text}");
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use tauri::State;
+
+use crate::ipc::{HeaderEntry, ProxyCommand};
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+/// Lets a request held by a breakpoint continue unchanged.
+#[tauri::command]
+pub fn resume_intercepted(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    flow_id: String,
+) -> Result<(), String> {
+    send_proxy_command(state, ipc_port, ProxyCommand::ResumeIntercepted { flow_id })
+}
+
+/// Sends a held request on with the given parts replaced; `None` keeps the original.
+#[tauri::command]
+pub fn modify_intercepted(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    flow_id: String,
+    method: Option<String>,
+    url: Option<String>,
+    headers: Option<Vec<HeaderEntry>>,
+    body: Option<String>,
+) -> Result<(), String> {
+    send_proxy_command(
+        state,
+        ipc_port,
+        ProxyCommand::ModifyIntercepted {
+            flow_id,
+            method,
+            url,
+            headers,
+            body,
+        },
+    )
+}
+
+/// Kills a held request without sending it; it is recorded as a failed flow.
+#[tauri::command]
+pub fn drop_intercepted(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    flow_id: String,
+) -> Result<(), String> {
+    send_proxy_command(state, ipc_port, ProxyCommand::DropIntercepted { flow_id })
+}
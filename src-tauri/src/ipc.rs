@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,6 +8,209 @@ pub struct HeaderEntry {
     pub value: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultipartPart {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub size: i64,
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlowTimings {
+    #[serde(default)]
+    pub dns_ms: Option<f64>,
+    /// TCP and TLS setup; only set on the first flow of each upstream connection.
+    #[serde(default)]
+    pub connect_ms: Option<f64>,
+    #[serde(default)]
+    pub tls_ms: Option<f64>,
+    #[serde(default)]
+    pub send_ms: Option<f64>,
+    #[serde(default)]
+    pub ttfb_ms: Option<f64>,
+    #[serde(default)]
+    pub download_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsHandshake {
+    #[serde(default)]
+    pub sni: Option<String>,
+    #[serde(default)]
+    pub alpn: Option<String>,
+    #[serde(default)]
+    pub tls_version: Option<String>,
+    #[serde(default)]
+    pub cipher: Option<String>,
+    #[serde(default)]
+    pub cert_subject: Option<String>,
+    #[serde(default)]
+    pub cert_issuer: Option<String>,
+    /// Leaf certificate expiry as Unix seconds.
+    #[serde(default)]
+    pub cert_not_after: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointChange {
+    pub endpoint: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayTargetResult {
+    pub base_url: String,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// Holds requests whose URL matches `url_pattern` (a substring, or a glob when it contains
+/// `*`, `?` or `[`) and, when set, whose method is `method`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakpointRule {
+    pub url_pattern: String,
+    #[serde(default)]
+    pub method: Option<String>,
+}
+
+/// Answers requests whose URL matches `url_pattern` with the contents of `local_path`. When
+/// `local_path` is a folder, the request path is resolved beneath it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapLocalRule {
+    #[serde(default)]
+    pub id: String,
+    pub url_pattern: String,
+    pub local_path: String,
+    /// Guessed from the file extension when unset.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Re-compress the file to match the request's `Accept-Encoding`.
+    #[serde(default)]
+    pub compress: bool,
+}
+
+/// Sends requests whose URL matches `url_pattern` to `target_url` instead, keeping the
+/// request path beneath the target's path (`api.prod.com/v1/x` -> `localhost:3000/v1/x`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapRemoteRule {
+    #[serde(default)]
+    pub id: String,
+    pub url_pattern: String,
+    pub target_url: String,
+    /// Keep the original Host header instead of the target's.
+    #[serde(default)]
+    pub preserve_host: bool,
+}
+
+/// Answers requests whose URL matches `url_pattern` (and `method`, when set) with a stubbed
+/// response; the request never reaches the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockRule {
+    #[serde(default)]
+    pub id: String,
+    pub url_pattern: String,
+    #[serde(default)]
+    pub method: Option<String>,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: Vec<HeaderEntry>,
+    #[serde(default)]
+    pub body: String,
+    /// Delay before the stub is returned.
+    #[serde(default)]
+    pub latency_ms: u32,
+    /// Compress the body to match the request's `Accept-Encoding`.
+    #[serde(default)]
+    pub compress: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FaultKind {
+    /// Kill the connection before anything is sent upstream.
+    #[serde(rename = "reset")]
+    Reset,
+    /// Hold the request, then kill it as if the server never answered.
+    #[serde(rename = "timeout")]
+    Timeout,
+    #[serde(rename = "http_500")]
+    Http500,
+    #[serde(rename = "http_503")]
+    Http503,
+}
+
+/// Fails requests whose URL matches `url_pattern`; the flow's `error` names the fault.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultRule {
+    pub url_pattern: String,
+    pub fault: FaultKind,
+}
+
+/// One difference between an edited replay and the flow it was edited from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestEdit {
+    /// `method`, `url`, `body`, or `header:<lowercased name>`.
+    pub field: String,
+    pub original: Option<String>,
+    pub modified: Option<String>,
+}
+
+/// Totals for one WebSocket connection, sent when it closes. `flow_id` is the upgrade flow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketFlow {
+    pub flow_id: String,
+    pub url: String,
+    pub host: String,
+    pub started: f64,
+    #[serde(default)]
+    pub ended: Option<f64>,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    #[serde(default)]
+    pub close_code: Option<u16>,
+    #[serde(default)]
+    pub close_reason: Option<String>,
+}
+
+/// One length-prefixed gRPC message. `json` is set when a registered descriptor knows the
+/// method; `error` explains a frame or message that could not be read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcMessage {
+    pub size: u64,
+    #[serde(default)]
+    pub json: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcDetail {
+    /// Fully qualified method name, when a registered descriptor has it.
+    #[serde(default)]
+    pub method: Option<String>,
+    pub request: Vec<GrpcMessage>,
+    pub response: Vec<GrpcMessage>,
+}
+
+/// The local process that opened a flow's client connection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub exe: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostCount {
+    pub host: String,
+    pub count: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlowRecord {
     pub id: String,
@@ -15,18 +220,113 @@ pub struct FlowRecord {
     pub method: String,
     pub url: String,
     pub host: String,
+    #[serde(default)]
+    pub host_header: Option<String>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub original_user_agent: Option<String>,
     pub path: String,
     pub scheme: String,
+    #[serde(default)]
+    pub server_port: Option<u16>,
+    #[serde(default)]
+    pub http_version: Option<String>,
+    /// `h1`, `h2` or `h3`.
+    #[serde(default)]
+    pub http_protocol: Option<String>,
+    /// HTTP/2 or HTTP/3 stream the exchange used, derived from request order on the connection.
+    #[serde(default)]
+    pub stream_id: Option<u64>,
+    /// `:method`, `:scheme`, `:authority`, `:path` and `:status` on HTTP/2 and HTTP/3.
+    #[serde(default)]
+    pub pseudo_headers: Option<Vec<HeaderEntry>>,
     pub status_code: i32,
     pub request_headers: Vec<HeaderEntry>,
     pub response_headers: Option<Vec<HeaderEntry>>,
+    #[serde(default)]
+    pub response_trailers: Option<Vec<HeaderEntry>>,
     pub request_body_size: i64,
     pub response_body_size: i64,
+    #[serde(default)]
+    pub response_body_decoded_size: Option<i64>,
+    #[serde(default)]
+    pub content_length_mismatch: Option<bool>,
     pub request_body: String,
     pub response_body: String,
     pub request_body_truncated: bool,
     pub response_body_truncated: bool,
     pub error: String,
+    #[serde(default)]
+    pub multipart_parts: Option<Vec<MultipartPart>>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub client_outcome: Option<String>,
+    #[serde(default)]
+    pub request_body_hash: Option<String>,
+    #[serde(default)]
+    pub response_body_hash: Option<String>,
+    #[serde(default)]
+    pub redirect_chain_id: Option<String>,
+    #[serde(default)]
+    pub redirect_index: Option<u32>,
+    #[serde(default)]
+    pub timings: Option<FlowTimings>,
+    /// Upstream TLS parameters, recorded for every HTTPS flow.
+    #[serde(default)]
+    pub tls_handshake: Option<TlsHandshake>,
+    /// PEM certificates the server presented, leaf first.
+    #[serde(default)]
+    pub certificate_chain: Vec<String>,
+    /// mitmproxy connection ids; flows sharing one reused a keep-alive connection.
+    #[serde(default)]
+    pub client_conn_id: Option<String>,
+    #[serde(default)]
+    pub server_conn_id: Option<String>,
+    #[serde(default)]
+    pub client_ip: Option<String>,
+    #[serde(default)]
+    pub client_port: Option<u16>,
+    /// Filled by the app from the Windows TCP table, not by the sidecar.
+    #[serde(default)]
+    pub process: Option<ProcessInfo>,
+    #[serde(default)]
+    pub client_ja3: Option<String>,
+    #[serde(default)]
+    pub client_ja4: Option<String>,
+    #[serde(default)]
+    pub client_hello_b64: Option<String>,
+    #[serde(default)]
+    pub replay_target: Option<String>,
+    #[serde(default)]
+    pub replay_of: Option<String>,
+    #[serde(default)]
+    pub replay_edits: Option<Vec<RequestEdit>>,
+    /// The URL a map-remote rule rewrote this request from.
+    #[serde(default)]
+    pub mapped_from: Option<String>,
+    /// Answered by a mock rule instead of the network.
+    #[serde(default)]
+    pub mocked: bool,
+    /// The response body was relayed as `stream_chunk` events instead of being buffered.
+    #[serde(default)]
+    pub streamed: bool,
+    /// Set on `application/grpc` flows.
+    #[serde(default)]
+    pub grpc: Option<GrpcDetail>,
+    /// Filled by the app for GraphQL POSTs; the type is `query`, `mutation` or `subscription`.
+    #[serde(default)]
+    pub graphql_operation_name: Option<String>,
+    #[serde(default)]
+    pub graphql_operation_type: Option<String>,
+    #[serde(default)]
+    pub graphql_variables: Option<serde_json::Value>,
+    /// Added by user scripts as the flow arrives.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub notes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,9 +335,22 @@ pub enum ProxyStatus {
     Starting,
     Running,
     Paused,
+    Passthrough,
+    #[serde(rename = "record_only")]
+    RecordOnly,
+    Degraded,
     Stopped,
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BindScope {
+    #[default]
+    Loopback,
+    /// `0.0.0.0`, so phones and other devices on the LAN can use the proxy.
+    All,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ProxyEvent {
@@ -46,22 +359,461 @@ pub enum ProxyEvent {
         status: ProxyStatus,
         message: Option<String>,
         port: Option<u16>,
+        #[serde(default)]
+        bind_address: Option<String>,
+        #[serde(default)]
+        detail: Option<String>,
+        #[serde(default)]
+        proxy_port: Option<u16>,
+        #[serde(default)]
+        ipc_port: Option<u16>,
+        /// Whether HTTP/3 and QUIC capture is enabled for the running proxy.
+        #[serde(default)]
+        http3: Option<bool>,
+        #[serde(default)]
+        transparent_processes: Vec<String>,
     },
     #[serde(rename = "error")]
     Error { message: String },
+    #[serde(rename = "line_overflow")]
+    LineOverflow { skipped_bytes: u64, limit_bytes: u64 },
+    /// Raised by `notify(message)` in a user script; emitted by the app, not the sidecar.
+    #[serde(rename = "script_notification")]
+    ScriptNotification {
+        flow_id: String,
+        script: String,
+        message: String,
+    },
+    #[serde(rename = "last_error")]
+    LastError {
+        message: Option<String>,
+        timestamp: Option<f64>,
+    },
     #[serde(rename = "flow")]
     Flow { record: FlowRecord },
+    #[serde(rename = "marker")]
+    Marker { timestamp: f64, label: String },
+    #[serde(rename = "flow_evicted")]
+    FlowEvicted { flow_id: String },
+    #[serde(rename = "flow_pinned")]
+    FlowPinned { flow_id: String, pinned: bool },
+    #[serde(rename = "direct_connection_hint")]
+    DirectConnectionHint {
+        reason: String,
+        host: Option<String>,
+    },
+    #[serde(rename = "service_state")]
+    ServiceState {
+        service: String,
+        running: bool,
+        address: Option<String>,
+    },
+    /// Reply to `GetLanAddresses`; empty when the machine has no usable network interface.
+    #[serde(rename = "lan_addresses")]
+    LanAddresses { addresses: Vec<String> },
+    #[serde(rename = "sidecar_info")]
+    SidecarInfo {
+        mitmproxy_version: String,
+        python_version: String,
+        platform: String,
+        #[serde(default)]
+        frozen: bool,
+        loaded_addons: Vec<String>,
+    },
+    #[serde(rename = "memory_usage")]
+    MemoryUsage {
+        rss_bytes: u64,
+        flow_count: u64,
+        total_body_bytes: u64,
+        #[serde(default)]
+        os_rss_bytes: Option<u64>,
+    },
+    #[serde(rename = "sequence_complete")]
+    SequenceComplete {
+        path: String,
+        total: u64,
+        succeeded: u64,
+        failed: u64,
+    },
+    #[serde(rename = "decoded_body")]
+    DecodedBody {
+        flow_id: String,
+        json: serde_json::Value,
+    },
+    #[serde(rename = "hexdump")]
+    Hexdump {
+        flow_id: String,
+        offset: u64,
+        lines: Vec<String>,
+    },
+    #[serde(rename = "baseline_diff")]
+    BaselineDiff {
+        baseline_path: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+        changed: Vec<EndpointChange>,
+    },
+    #[serde(rename = "fuzz_summary")]
+    FuzzSummary {
+        flow_id: String,
+        field: String,
+        total: u64,
+        status_counts: BTreeMap<String, u64>,
+        failed: u64,
+    },
+    #[serde(rename = "replay_multi_summary")]
+    ReplayMultiSummary {
+        flow_id: String,
+        results: Vec<ReplayTargetResult>,
+        diverged: bool,
+    },
+    #[serde(rename = "replay_progress")]
+    ReplayProgress {
+        flow_id: String,
+        completed: u64,
+        total: u64,
+    },
+    #[serde(rename = "export_complete")]
+    ExportComplete {
+        kind: String,
+        path: String,
+        count: u64,
+        #[serde(default)]
+        redacted: Option<u64>,
+    },
+    #[serde(rename = "capture_summary")]
+    CaptureSummary {
+        total_flows: u64,
+        total_bytes: u64,
+        error_count: u64,
+        top_hosts: Vec<HostCount>,
+        duration_ms: u64,
+    },
+    /// One WebSocket frame. `direction` is `client` or `server` (the sender); text frames
+    /// (opcode 1) carry the text and other frames carry base64.
+    #[serde(rename = "websocket_message")]
+    WebSocketMessage {
+        flow_id: String,
+        direction: String,
+        opcode: u8,
+        payload: String,
+        timestamp: f64,
+        #[serde(default)]
+        truncated: bool,
+    },
+    #[serde(rename = "websocket_closed")]
+    WebSocketClosed { summary: WebSocketFlow },
+    /// Part of a `text/event-stream` response, sent as it arrives. The flow itself is only
+    /// emitted when the stream ends, with `streamed` set and no response body.
+    #[serde(rename = "stream_chunk")]
+    StreamChunk {
+        flow_id: String,
+        sequence: u64,
+        data: String,
+        timestamp: f64,
+    },
+    /// A request matched a breakpoint and is held until resumed, modified or dropped.
+    #[serde(rename = "intercepted")]
+    Intercepted {
+        flow_id: String,
+        method: String,
+        url: String,
+        request_headers: Vec<HeaderEntry>,
+        #[serde(default)]
+        request_body: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ProxyCommand {
     #[serde(rename = "start")]
-    Start { port: u16 },
+    Start {
+        port: u16,
+        #[serde(default)]
+        bind_scope: BindScope,
+        #[serde(default)]
+        auto_port: bool,
+        /// Must be set with `BindScope::All`; exposing the proxy to the network is never implicit.
+        #[serde(default)]
+        confirm_lan: bool,
+        /// Enables mitmproxy's HTTP/3 support instead of keeping clients on TCP.
+        #[serde(default)]
+        http3: bool,
+        /// Process names or PIDs whose traffic is redirected into the proxy with WinDivert,
+        /// for apps that ignore proxy settings. Needs elevation; empty disables it.
+        #[serde(default)]
+        transparent_processes: Vec<String>,
+    },
     #[serde(rename = "stop")]
-    Stop,
+    Stop {
+        #[serde(default)]
+        skip_summary: bool,
+    },
     #[serde(rename = "pause")]
     Pause,
     #[serde(rename = "resume")]
     Resume,
+    #[serde(rename = "passthrough")]
+    Passthrough { enabled: bool },
+    #[serde(rename = "mute_hosts")]
+    MuteHosts { patterns: Vec<String> },
+    #[serde(rename = "unmute_hosts")]
+    UnmuteHosts { patterns: Vec<String> },
+    #[serde(rename = "clear")]
+    Clear,
+    #[serde(rename = "pin_flow")]
+    PinFlow { flow_id: String, pinned: bool },
+    #[serde(rename = "mark_flow_outcome")]
+    MarkFlowOutcome { flow_id: String, outcome: String },
+    #[serde(rename = "clear_matching")]
+    ClearMatching { patterns: Vec<String> },
+    #[serde(rename = "body_capture_toggles")]
+    BodyCaptureToggles {
+        capture_request: bool,
+        capture_response: bool,
+    },
+    #[serde(rename = "set_body_policy")]
+    SetBodyPolicy { store_limit: u64, stream_limit: u64 },
+    #[serde(rename = "replay_sequence")]
+    ReplaySequence {
+        path: String,
+        #[serde(default)]
+        base_url: Option<String>,
+        #[serde(default)]
+        preserve_host: bool,
+    },
+    #[serde(rename = "get_last_error")]
+    GetLastError,
+    #[serde(rename = "clear_last_error")]
+    ClearLastError,
+    #[serde(rename = "sni_override")]
+    SniOverride { host: String, sni: String },
+    #[serde(rename = "set_request_timeout")]
+    SetRequestTimeout { seconds: u32 },
+    #[serde(rename = "resolve_timings")]
+    ResolveTimings { enabled: bool },
+    #[serde(rename = "show_preflight")]
+    ShowPreflight { enabled: bool },
+    #[serde(rename = "replay_with_timing")]
+    ReplayWithTiming { flow_ids: Vec<String>, speed: f64 },
+    #[serde(rename = "replay_fuzz")]
+    ReplayFuzz {
+        flow_id: String,
+        field: String,
+        mutations: Vec<String>,
+    },
+    #[serde(rename = "replay_multi")]
+    ReplayMulti {
+        flow_id: String,
+        base_urls: Vec<String>,
+        #[serde(default)]
+        preserve_host: bool,
+    },
+    #[serde(rename = "send_raw")]
+    SendRaw {
+        raw_request: String,
+        scheme: String,
+        host: String,
+        port: u16,
+    },
+    /// Reports upstream TLS handshake failures for hosts matching `host`; empty clears the list.
+    /// Handshakes are recorded on every HTTPS flow either way.
+    #[serde(rename = "capture_handshake")]
+    CaptureHandshake { host: String },
+    #[serde(rename = "replay_handshake")]
+    ReplayHandshake { flow_id: String },
+    #[serde(rename = "replay_flow")]
+    ReplayFlow { id: String },
+    #[serde(rename = "set_map_local_rules")]
+    SetMapLocalRules { rules: Vec<MapLocalRule> },
+    #[serde(rename = "set_map_remote_rules")]
+    SetMapRemoteRules { rules: Vec<MapRemoteRule> },
+    #[serde(rename = "set_mock_rules")]
+    SetMockRules { rules: Vec<MockRule> },
+    #[serde(rename = "set_fault_rules")]
+    SetFaultRules { rules: Vec<FaultRule> },
+    #[serde(rename = "set_breakpoints")]
+    SetBreakpoints { rules: Vec<BreakpointRule> },
+    #[serde(rename = "resume_intercepted")]
+    ResumeIntercepted { flow_id: String },
+    #[serde(rename = "modify_intercepted")]
+    ModifyIntercepted {
+        flow_id: String,
+        #[serde(default)]
+        method: Option<String>,
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        headers: Option<Vec<HeaderEntry>>,
+        #[serde(default)]
+        body: Option<String>,
+    },
+    #[serde(rename = "drop_intercepted")]
+    DropIntercepted { flow_id: String },
+    #[serde(rename = "edit_and_replay")]
+    EditAndReplay {
+        parent_id: String,
+        method: String,
+        url: String,
+        #[serde(default)]
+        headers: Vec<HeaderEntry>,
+        #[serde(default)]
+        body: String,
+    },
+    #[serde(rename = "send_request")]
+    SendRequest {
+        method: String,
+        url: String,
+        #[serde(default)]
+        headers: Vec<HeaderEntry>,
+        #[serde(default)]
+        body: String,
+        #[serde(default)]
+        direct: bool,
+    },
+    #[serde(rename = "set_auth_refresh")]
+    SetAuthRefresh {
+        token_header: String,
+        refresh_flow_id: String,
+        extract_json_path: String,
+    },
+    #[serde(rename = "strip_accept_encoding")]
+    StripAcceptEncoding { enabled: bool },
+    #[serde(rename = "capture_tls_fingerprints")]
+    CaptureTlsFingerprints { enabled: bool },
+    #[serde(rename = "capture_client_hello")]
+    CaptureClientHello { enabled: bool },
+    #[serde(rename = "port_filter")]
+    PortFilter { allowed_ports: Vec<u16> },
+    #[serde(rename = "brotli_decode")]
+    BrotliDecode { enabled: bool },
+    #[serde(rename = "set_user_agent")]
+    SetUserAgent {
+        #[serde(default)]
+        value: Option<String>,
+    },
+    #[serde(rename = "connection_reuse")]
+    ConnectionReuse { enabled: bool },
+    #[serde(rename = "record_only")]
+    RecordOnly { canned_status: u16 },
+    #[serde(rename = "throttle_hosts")]
+    ThrottleHosts {
+        patterns: Vec<String>,
+        #[serde(default)]
+        down_kbps: Option<u32>,
+        #[serde(default)]
+        latency_ms: Option<u32>,
+    },
+    #[serde(rename = "clear_host_throttles")]
+    ClearHostThrottles,
+    /// Throttles every connection; 0 leaves that limit off. Host throttles take precedence.
+    #[serde(rename = "set_throttle")]
+    SetThrottle {
+        #[serde(default)]
+        download_kbps: u32,
+        #[serde(default)]
+        upload_kbps: u32,
+        #[serde(default)]
+        latency_ms: u32,
+    },
+    #[serde(rename = "latency_by_status")]
+    LatencyByStatus { rules: Vec<(u16, u32)> },
+    #[serde(rename = "clear_latency_rules")]
+    ClearLatencyRules,
+    #[serde(rename = "set_dns_servers")]
+    SetDnsServers { servers: Vec<String> },
+    #[serde(rename = "scope_to_client")]
+    ScopeToClient { token: String },
+    /// Records only flows from processes with one of these PIDs or executable names; both
+    /// empty records every process.
+    #[serde(rename = "set_process_filter")]
+    SetProcessFilter {
+        #[serde(default)]
+        pids: Vec<u32>,
+        #[serde(default)]
+        names: Vec<String>,
+    },
+    #[serde(rename = "pause_on_error")]
+    PauseOnError {
+        enabled: bool,
+        #[serde(default)]
+        status_min: Option<u16>,
+    },
+    #[serde(rename = "force_http1")]
+    ForceHttp1 { hosts: Vec<String> },
+    #[serde(rename = "dedupe_by_endpoint")]
+    DedupeByEndpoint { keep: String },
+    #[serde(rename = "add_marker")]
+    AddMarker { label: String },
+    #[serde(rename = "get_sidecar_info")]
+    GetSidecarInfo,
+    #[serde(rename = "get_memory_usage")]
+    GetMemoryUsage,
+    #[serde(rename = "export_waterfall")]
+    ExportWaterfall { path: String },
+    #[serde(rename = "export_postman")]
+    ExportPostman {
+        path: String,
+        #[serde(default)]
+        filter: Option<String>,
+    },
+    #[serde(rename = "compare_baseline")]
+    CompareBaseline { baseline_path: String },
+    #[serde(rename = "start_tail")]
+    StartTail { host_pattern: String, path: String },
+    #[serde(rename = "stop_tail")]
+    StopTail,
+    #[serde(rename = "set_proto_descriptor")]
+    SetProtoDescriptor { descriptor_path: String },
+    #[serde(rename = "decode_proto")]
+    DecodeProto { flow_id: String },
+    #[serde(rename = "get_hexdump")]
+    GetHexdump {
+        flow_id: String,
+        direction: String,
+        offset: u64,
+        length: u64,
+    },
+    #[serde(rename = "save_response_body")]
+    SaveResponseBody { flow_id: String, path: String },
+    #[serde(rename = "export_openapi")]
+    ExportOpenApi {
+        path: String,
+        #[serde(default)]
+        filter: Option<String>,
+    },
+    #[serde(rename = "export_session_diff")]
+    ExportSessionDiff {
+        baseline_path: String,
+        out_path: String,
+    },
+    #[serde(rename = "export_redacted_har")]
+    ExportRedactedHar {
+        path: String,
+        header_names: Vec<String>,
+        body_patterns: Vec<String>,
+    },
+    #[serde(rename = "start_http_bridge")]
+    StartHttpBridge { port: u16 },
+    #[serde(rename = "stop_http_bridge")]
+    StopHttpBridge,
+    #[serde(rename = "start_metrics_endpoint")]
+    StartMetricsEndpoint { port: u16 },
+    #[serde(rename = "stop_metrics_endpoint")]
+    StopMetricsEndpoint,
+    /// Serves a PAC file; hosts matching `exclude` go direct, and a non-empty `include`
+    /// limits proxying to matching hosts. Bare names also match their subdomains.
+    #[serde(rename = "start_pac_server")]
+    StartPacServer {
+        port: u16,
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+    },
+    #[serde(rename = "stop_pac_server")]
+    StopPacServer,
+    #[serde(rename = "get_lan_addresses")]
+    GetLanAddresses,
 }
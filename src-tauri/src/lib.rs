@@ -1,7 +1,22 @@
+mod certs;
+mod composer;
+mod connections;
+mod filter;
+mod flow_store;
+mod graphql;
+mod grpc;
+mod har;
+mod intercept;
 mod ipc;
+mod process;
+mod rules;
+mod scripts;
+mod search;
+mod settings;
 mod sidecar;
 mod sidecar_client;
 mod system;
+mod throttle;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -20,15 +35,59 @@ pub fn run() {
     .plugin(tauri_plugin_fs::init())
     .manage(sidecar::SidecarState::default())
     .manage(sidecar_client::SidecarClientState::default())
+    .manage(flow_store::FlowStoreState::default())
+    .manage(scripts::ScriptState::default())
     .invoke_handler(tauri::generate_handler![
       sidecar::start_sidecar,
       sidecar::stop_sidecar,
+      sidecar::diagnose_startup,
       sidecar_client::start_sidecar_listener,
       sidecar_client::send_proxy_command,
+      sidecar_client::replay_flow,
+      sidecar_client::edit_and_replay,
+      sidecar_client::get_bound_ports,
+      composer::send_request,
+      process::set_process_filter,
+      certs::get_certificate_chain,
+      connections::list_connections,
+      intercept::resume_intercepted,
+      intercept::modify_intercepted,
+      intercept::drop_intercepted,
+      rules::list_map_local_rules,
+      rules::set_map_local_rules,
+      rules::upsert_map_local_rule,
+      rules::delete_map_local_rule,
+      rules::list_map_remote_rules,
+      rules::set_map_remote_rules,
+      rules::upsert_map_remote_rule,
+      rules::delete_map_remote_rule,
+      rules::list_mock_rules,
+      rules::set_mock_rules,
+      rules::upsert_mock_rule,
+      rules::delete_mock_rule,
+      scripts::add_script,
+      scripts::remove_script,
+      scripts::list_scripts,
+      throttle::set_network_preset,
+      grpc::register_proto_descriptors,
+      graphql::list_graphql_operations,
+      har::import_har,
+      flow_store::query_flows,
+      flow_store::get_flow,
+      filter::filter_flows,
+      search::search_flows,
+      settings::save_settings,
+      settings::load_settings,
       system::open_cert_folder,
       system::install_cert,
+      system::export_cert,
       system::uninstall_cert,
-      system::open_browser
+      system::list_browsers,
+      system::open_browser,
+      system::enable_system_proxy,
+      system::disable_system_proxy,
+      system::enable_pac_system_proxy,
+      system::verify_interception
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
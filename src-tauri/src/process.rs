@@ -0,0 +1,153 @@
+use std::net::IpAddr;
+
+use tauri::State;
+
+use crate::ipc::{FlowRecord, ProcessInfo, ProxyCommand};
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+/// Fills `record.process` with the local process that owns the flow's client socket.
+///
+/// Only loopback clients are looked up; a LAN client's port says nothing about local
+/// processes. The lookup runs when the flow completes, so a client that has already closed
+/// its connection is not attributed.
+pub fn enrich(record: &mut FlowRecord, proxy_port: Option<u16>) {
+    let (Some(ip), Some(port), Some(proxy_port)) =
+        (record.client_ip.as_deref(), record.client_port, proxy_port)
+    else {
+        return;
+    };
+    if !ip.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback()) {
+        return;
+    }
+    record.process = owning_pid(port, proxy_port).map(process_info);
+}
+
+fn process_info(pid: u32) -> ProcessInfo {
+    let exe = image_path(pid);
+    let name = exe.as_deref().and_then(|exe| {
+        exe.rsplit(['\\', '/'])
+            .next()
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    });
+    ProcessInfo { pid, name, exe }
+}
+
+/// Limits capture to the given processes; `names` match executable names with or without
+/// `.exe`. Connections opened before the filter changes keep their previous treatment.
+#[tauri::command]
+pub fn set_process_filter(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    pids: Vec<u32>,
+    names: Vec<String>,
+) -> Result<(), String> {
+    let names: Vec<String> = names
+        .iter()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    send_proxy_command(
+        state,
+        ipc_port,
+        ProxyCommand::SetProcessFilter { pids, names },
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn tcp_table(family: u16) -> Option<Vec<u32>> {
+    use windows_sys::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetExtendedTcpTable, TCP_TABLE_OWNER_PID_ALL,
+    };
+
+    let mut size = 0u32;
+    // The table grows between calls when connections open, so retry a few times.
+    for _ in 0..4 {
+        // u32 elements keep the buffer aligned for the row structs.
+        let mut buffer = vec![0u32; (size as usize).div_ceil(4)];
+        let result = unsafe {
+            GetExtendedTcpTable(
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+                0,
+                u32::from(family),
+                TCP_TABLE_OWNER_PID_ALL,
+                0,
+            )
+        };
+        match result {
+            NO_ERROR => return Some(buffer),
+            ERROR_INSUFFICIENT_BUFFER => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Finds the process whose socket on local `port` is connected to the proxy, searching the
+/// IPv4 and IPv6 TCP tables.
+#[cfg(target_os = "windows")]
+fn owning_pid(port: u16, proxy_port: u16) -> Option<u32> {
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        MIB_TCP6TABLE_OWNER_PID, MIB_TCPTABLE_OWNER_PID,
+    };
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+    // Ports are stored in network byte order in the low 16 bits.
+    let matches = |local_port: u32, remote_port: u32| {
+        u16::from_be(local_port as u16) == port && u16::from_be(remote_port as u16) == proxy_port
+    };
+
+    if let Some(buffer) = tcp_table(AF_INET) {
+        let table = buffer.as_ptr().cast::<MIB_TCPTABLE_OWNER_PID>();
+        let rows = unsafe {
+            std::slice::from_raw_parts((*table).table.as_ptr(), (*table).dwNumEntries as usize)
+        };
+        if let Some(row) = rows
+            .iter()
+            .find(|row| matches(row.dwLocalPort, row.dwRemotePort))
+        {
+            return Some(row.dwOwningPid);
+        }
+    }
+    let buffer = tcp_table(AF_INET6)?;
+    let table = buffer.as_ptr().cast::<MIB_TCP6TABLE_OWNER_PID>();
+    let rows = unsafe {
+        std::slice::from_raw_parts((*table).table.as_ptr(), (*table).dwNumEntries as usize)
+    };
+    rows.iter()
+        .find(|row| matches(row.dwLocalPort, row.dwRemotePort))
+        .map(|row| row.dwOwningPid)
+}
+
+#[cfg(target_os = "windows")]
+fn image_path(pid: u32) -> Option<String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if handle.is_null() {
+        return None;
+    }
+    let mut path = [0u16; 1024];
+    let mut len = path.len() as u32;
+    let ok = unsafe {
+        QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, path.as_mut_ptr(), &mut len)
+    };
+    unsafe { CloseHandle(handle) };
+    (ok != 0).then(|| String::from_utf16_lossy(&path[..len as usize]))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn owning_pid(_port: u16, _proxy_port: u16) -> Option<u32> {
+    None
+}
+
+#[cfg(not(target_os = "windows"))]
+fn image_path(_pid: u32) -> Option<String> {
+    None
+}
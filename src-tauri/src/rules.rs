@@ -0,0 +1,355 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::ipc::{MapLocalRule, MapRemoteRule, MockRule, ProxyCommand};
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+const RULES_FILE: &str = "rules.json";
+
+/// Rule sets kept in the app data folder and pushed to every sidecar that connects.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedRules {
+    #[serde(default)]
+    map_local: Vec<MapLocalRule>,
+    #[serde(default)]
+    map_remote: Vec<MapRemoteRule>,
+    #[serde(default)]
+    mock: Vec<MockRule>,
+}
+
+impl SavedRules {
+    fn commands(self) -> [ProxyCommand; 3] {
+        [
+            ProxyCommand::SetMapLocalRules {
+                rules: self.map_local,
+            },
+            ProxyCommand::SetMapRemoteRules {
+                rules: self.map_remote,
+            },
+            ProxyCommand::SetMockRules { rules: self.mock },
+        ]
+    }
+}
+
+fn rules_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir: PathBuf = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Unable to resolve app data folder: {err}"))?;
+    Ok(dir.join(RULES_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<SavedRules, String> {
+    let path = rules_path(app)?;
+    if !path.exists() {
+        return Ok(SavedRules::default());
+    }
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("Unable to read {}: {err}", path.display()))?;
+    serde_json::from_str(&text).map_err(|err| format!("{} is not valid: {err}", path.display()))
+}
+
+fn save(app: &AppHandle, rules: &SavedRules) -> Result<(), String> {
+    let path = rules_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Unable to create {}: {err}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(rules).map_err(|err| err.to_string())?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, text).map_err(|err| format!("Unable to write {}: {err}", temp.display()))?;
+    fs::rename(&temp, &path).map_err(|err| format!("Unable to save {}: {err}", path.display()))
+}
+
+fn new_rule_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    format!("rule-{nanos:x}")
+}
+
+/// A rule kept in the rules file and addressed by a generated id.
+trait SavedRule {
+    fn id(&self) -> &str;
+    fn id_mut(&mut self) -> &mut String;
+    fn validate(&self) -> Result<(), String>;
+}
+
+impl SavedRule for MapLocalRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut String {
+        &mut self.id
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.url_pattern.trim().is_empty() {
+            return Err("Map-local rule needs a URL pattern.".into());
+        }
+        if !Path::new(self.local_path.trim()).exists() {
+            return Err(format!("Local file not found: {}", self.local_path));
+        }
+        Ok(())
+    }
+}
+
+impl SavedRule for MapRemoteRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut String {
+        &mut self.id
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.url_pattern.trim().is_empty() {
+            return Err("Map-remote rule needs a URL pattern.".into());
+        }
+        let target = self.target_url.trim();
+        if !(target.starts_with("http://") || target.starts_with("https://")) {
+            return Err(format!("Map-remote target must be an http(s) URL: {target}"));
+        }
+        Ok(())
+    }
+}
+
+impl SavedRule for MockRule {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn id_mut(&mut self) -> &mut String {
+        &mut self.id
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.url_pattern.trim().is_empty() {
+            return Err("Mock rule needs a URL pattern.".into());
+        }
+        if !(100..=599).contains(&self.status) {
+            return Err(format!("Mock status must be 100-599, not {}", self.status));
+        }
+        Ok(())
+    }
+}
+
+/// Validates `rule` and gives it an id if it has none.
+fn prepare<R: SavedRule>(mut rule: R) -> Result<R, String> {
+    rule.validate()?;
+    let id = rule.id_mut();
+    if id.is_empty() {
+        *id = new_rule_id();
+    }
+    Ok(rule)
+}
+
+/// Adds `rule`, or replaces the rule with the same id.
+fn upsert<R: SavedRule>(rules: &mut Vec<R>, rule: R) -> Result<(), String> {
+    let rule = prepare(rule)?;
+    match rules.iter_mut().find(|existing| existing.id() == rule.id()) {
+        Some(existing) => *existing = rule,
+        None => rules.push(rule),
+    }
+    Ok(())
+}
+
+/// Saves `saved` and sends `command` to the sidecar if one has connected; otherwise the
+/// saved rules are pushed when it does.
+fn store(
+    app: &AppHandle,
+    state: State<SidecarClientState>,
+    saved: &SavedRules,
+    command: ProxyCommand,
+) -> Result<(), String> {
+    save(app, saved)?;
+    match state.bound_ports().ipc_port {
+        Some(ipc_port) => send_proxy_command(state, ipc_port, command),
+        None => Ok(()),
+    }
+}
+
+fn store_map_local(
+    app: &AppHandle,
+    state: State<SidecarClientState>,
+    saved: SavedRules,
+) -> Result<Vec<MapLocalRule>, String> {
+    let rules = saved.map_local.clone();
+    store(
+        app,
+        state,
+        &saved,
+        ProxyCommand::SetMapLocalRules {
+            rules: rules.clone(),
+        },
+    )?;
+    Ok(rules)
+}
+
+fn store_map_remote(
+    app: &AppHandle,
+    state: State<SidecarClientState>,
+    saved: SavedRules,
+) -> Result<Vec<MapRemoteRule>, String> {
+    let rules = saved.map_remote.clone();
+    store(
+        app,
+        state,
+        &saved,
+        ProxyCommand::SetMapRemoteRules {
+            rules: rules.clone(),
+        },
+    )?;
+    Ok(rules)
+}
+
+fn store_mock(
+    app: &AppHandle,
+    state: State<SidecarClientState>,
+    saved: SavedRules,
+) -> Result<Vec<MockRule>, String> {
+    let rules = saved.mock.clone();
+    store(
+        app,
+        state,
+        &saved,
+        ProxyCommand::SetMockRules {
+            rules: rules.clone(),
+        },
+    )?;
+    Ok(rules)
+}
+
+/// Pushes every saved rule set to a sidecar that has just connected on `ipc_port`.
+pub(crate) fn push_saved(app: &AppHandle, ipc_port: u16) -> Result<(), String> {
+    for command in load(app)?.commands() {
+        send_proxy_command(app.state(), ipc_port, command)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_map_local_rules(app: AppHandle) -> Result<Vec<MapLocalRule>, String> {
+    Ok(load(&app)?.map_local)
+}
+
+/// Replaces every map-local rule; rules without an id are given one.
+#[tauri::command]
+pub fn set_map_local_rules(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    rules: Vec<MapLocalRule>,
+) -> Result<Vec<MapLocalRule>, String> {
+    let mut saved = load(&app)?;
+    saved.map_local = rules.into_iter().map(prepare).collect::<Result<_, _>>()?;
+    store_map_local(&app, state, saved)
+}
+
+/// Adds `rule`, or replaces the rule with the same id.
+#[tauri::command]
+pub fn upsert_map_local_rule(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    rule: MapLocalRule,
+) -> Result<Vec<MapLocalRule>, String> {
+    let mut saved = load(&app)?;
+    upsert(&mut saved.map_local, rule)?;
+    store_map_local(&app, state, saved)
+}
+
+#[tauri::command]
+pub fn delete_map_local_rule(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    id: String,
+) -> Result<Vec<MapLocalRule>, String> {
+    let mut saved = load(&app)?;
+    saved.map_local.retain(|rule| rule.id != id);
+    store_map_local(&app, state, saved)
+}
+
+#[tauri::command]
+pub fn list_map_remote_rules(app: AppHandle) -> Result<Vec<MapRemoteRule>, String> {
+    Ok(load(&app)?.map_remote)
+}
+
+/// Replaces every map-remote rule; rules without an id are given one.
+#[tauri::command]
+pub fn set_map_remote_rules(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    rules: Vec<MapRemoteRule>,
+) -> Result<Vec<MapRemoteRule>, String> {
+    let mut saved = load(&app)?;
+    saved.map_remote = rules.into_iter().map(prepare).collect::<Result<_, _>>()?;
+    store_map_remote(&app, state, saved)
+}
+
+/// Adds `rule`, or replaces the rule with the same id.
+#[tauri::command]
+pub fn upsert_map_remote_rule(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    rule: MapRemoteRule,
+) -> Result<Vec<MapRemoteRule>, String> {
+    let mut saved = load(&app)?;
+    upsert(&mut saved.map_remote, rule)?;
+    store_map_remote(&app, state, saved)
+}
+
+#[tauri::command]
+pub fn delete_map_remote_rule(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    id: String,
+) -> Result<Vec<MapRemoteRule>, String> {
+    let mut saved = load(&app)?;
+    saved.map_remote.retain(|rule| rule.id != id);
+    store_map_remote(&app, state, saved)
+}
+
+#[tauri::command]
+pub fn list_mock_rules(app: AppHandle) -> Result<Vec<MockRule>, String> {
+    Ok(load(&app)?.mock)
+}
+
+/// Replaces every mock rule; rules without an id are given one.
+#[tauri::command]
+pub fn set_mock_rules(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    rules: Vec<MockRule>,
+) -> Result<Vec<MockRule>, String> {
+    let mut saved = load(&app)?;
+    saved.mock = rules.into_iter().map(prepare).collect::<Result<_, _>>()?;
+    store_mock(&app, state, saved)
+}
+
+/// Adds `rule`, or replaces the rule with the same id.
+#[tauri::command]
+pub fn upsert_mock_rule(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    rule: MockRule,
+) -> Result<Vec<MockRule>, String> {
+    let mut saved = load(&app)?;
+    upsert(&mut saved.mock, rule)?;
+    store_mock(&app, state, saved)
+}
+
+#[tauri::command]
+pub fn delete_mock_rule(
+    app: AppHandle,
+    state: State<SidecarClientState>,
+    id: String,
+) -> Result<Vec<MockRule>, String> {
+    let mut saved = load(&app)?;
+    saved.mock.retain(|rule| rule.id != id);
+    store_mock(&app, state, saved)
+}
@@ -0,0 +1,185 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rhai::{Engine, Scope, AST};
+use serde::Serialize;
+use tauri::State;
+
+use crate::ipc::FlowRecord;
+
+/// Stops a runaway script (e.g. an endless loop) from stalling the event listener.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// What the scripts asked for while handling one flow.
+#[derive(Default)]
+struct Effects {
+    tags: Vec<String>,
+    notes: Vec<String>,
+    notifications: Vec<String>,
+    drop: bool,
+}
+
+struct Script {
+    id: String,
+    name: String,
+    source: String,
+    ast: AST,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptInfo {
+    pub id: String,
+    pub name: String,
+    pub source: String,
+}
+
+/// A notification raised by `notify(message)` in a script.
+pub struct ScriptNotification {
+    pub script: String,
+    pub message: String,
+}
+
+/// User scripts run on every flow the listener receives. Each sees the record as `flow` and
+/// can call `tag(name)`, `annotate(text)`, `notify(message)` and `drop_flow()`.
+pub struct ScriptState {
+    engine: Engine,
+    effects: Arc<Mutex<Effects>>,
+    scripts: Mutex<Vec<Script>>,
+}
+
+fn lock(effects: &Mutex<Effects>) -> MutexGuard<'_, Effects> {
+    effects
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl Default for ScriptState {
+    fn default() -> Self {
+        let effects = Arc::new(Mutex::new(Effects::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let shared = effects.clone();
+        engine.register_fn("tag", move |name: &str| {
+            lock(&shared).tags.push(name.into())
+        });
+        let shared = effects.clone();
+        engine.register_fn("annotate", move |text: &str| {
+            lock(&shared).notes.push(text.into())
+        });
+        let shared = effects.clone();
+        engine.register_fn("notify", move |message: &str| {
+            lock(&shared).notifications.push(message.into())
+        });
+        let shared = effects.clone();
+        engine.register_fn("drop_flow", move || lock(&shared).drop = true);
+        Self {
+            engine,
+            effects,
+            scripts: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl ScriptState {
+    /// Runs every script on `record`, adding tags and notes to it. Returns `None` when a
+    /// script dropped the flow, otherwise the notifications raised.
+    pub fn apply(&self, record: &mut FlowRecord) -> Option<Vec<ScriptNotification>> {
+        let Ok(scripts) = self.scripts.lock() else {
+            return Some(Vec::new());
+        };
+        if scripts.is_empty() {
+            return Some(Vec::new());
+        }
+        let flow = match rhai::serde::to_dynamic(&*record) {
+            Ok(flow) => flow,
+            Err(err) => {
+                log::warn!("Flow {} not passed to scripts: {err}", record.id);
+                return Some(Vec::new());
+            }
+        };
+        let mut notifications = Vec::new();
+        for script in scripts.iter() {
+            *lock(&self.effects) = Effects::default();
+            let mut scope = Scope::new();
+            scope.push_constant("flow", flow.clone());
+            if let Err(err) = self.engine.run_ast_with_scope(&mut scope, &script.ast) {
+                log::warn!("Script {} failed on flow {}: {err}", script.name, record.id);
+            }
+            let effects = std::mem::take(&mut *lock(&self.effects));
+            record.tags.extend(effects.tags);
+            record.notes.extend(effects.notes);
+            notifications.extend(effects.notifications.into_iter().map(|message| {
+                ScriptNotification {
+                    script: script.name.clone(),
+                    message,
+                }
+            }));
+            if effects.drop {
+                return None;
+            }
+        }
+        Some(notifications)
+    }
+}
+
+/// Compiles and registers a script; syntax errors are returned without registering it.
+#[tauri::command]
+pub fn add_script(
+    state: State<ScriptState>,
+    name: String,
+    source: String,
+) -> Result<ScriptInfo, String> {
+    let ast = state
+        .engine
+        .compile(&source)
+        .map_err(|err| format!("Script does not compile: {err}"))?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let name = match name.trim() {
+        "" => "script".to_string(),
+        trimmed => trimmed.to_string(),
+    };
+    let script = Script {
+        id: format!("script-{nanos:x}"),
+        name,
+        source,
+        ast,
+    };
+    let info = ScriptInfo {
+        id: script.id.clone(),
+        name: script.name.clone(),
+        source: script.source.clone(),
+    };
+    state
+        .scripts
+        .lock()
+        .map_err(|_| "Script lock poisoned")?
+        .push(script);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn remove_script(state: State<ScriptState>, id: String) -> Result<(), String> {
+    let mut scripts = state.scripts.lock().map_err(|_| "Script lock poisoned")?;
+    let before = scripts.len();
+    scripts.retain(|script| script.id != id);
+    if scripts.len() == before {
+        return Err(format!("No script with id {id}"));
+    }
+    Ok(())
+}
+
+/// Registered scripts in the order they run.
+#[tauri::command]
+pub fn list_scripts(state: State<ScriptState>) -> Result<Vec<ScriptInfo>, String> {
+    let scripts = state.scripts.lock().map_err(|_| "Script lock poisoned")?;
+    Ok(scripts
+        .iter()
+        .map(|script| ScriptInfo {
+            id: script.id.clone(),
+            name: script.name.clone(),
+            source: script.source.clone(),
+        })
+        .collect())
+}
@@ -0,0 +1,195 @@
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::flow_store::FlowStoreState;
+
+const MAX_SEARCH_RESULTS: u32 = 500;
+/// Searchable `flows_fts` columns, in table order.
+const FIELDS: [&str; 4] = ["url", "headers", "request_body", "response_body"];
+/// Private-use characters `highlight()` wraps each matched token in.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Highlight {
+    pub field: String,
+    /// Byte offsets into the field's text.
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub flow_id: String,
+    pub highlights: Vec<Highlight>,
+}
+
+/// Quotes each whitespace-separated term so user input is never parsed as FTS5 syntax.
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Byte ranges of the tokens `highlight()` marked in `marked`, relative to the text without
+/// the markers.
+fn marked_ranges(field: &str, marked: &str) -> Vec<Highlight> {
+    let mut found = Vec::new();
+    let mut removed = 0;
+    let mut start = 0;
+    for (index, ch) in marked.char_indices() {
+        if ch == MATCH_START {
+            start = index - removed;
+        } else if ch == MATCH_END {
+            found.push(Highlight {
+                field: field.to_string(),
+                start,
+                end: index - removed,
+            });
+        } else {
+            continue;
+        }
+        removed += ch.len_utf8();
+    }
+    found
+}
+
+fn search_hit(row: &Row) -> rusqlite::Result<SearchHit> {
+    let mut highlights = Vec::new();
+    for (index, field) in FIELDS.into_iter().enumerate() {
+        let marked: Option<String> = row.get(index + 1)?;
+        highlights.extend(marked_ranges(field, &marked.unwrap_or_default()));
+    }
+    Ok(SearchHit {
+        flow_id: row.get(0)?,
+        highlights,
+    })
+}
+
+/// Runs the search with FTS5 marking the matched tokens, so highlights follow the same
+/// tokenizer as the match itself.
+fn search(conn: &Connection, query: &str) -> rusqlite::Result<Vec<SearchHit>> {
+    let match_expr = fts_query(query);
+    if match_expr.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut statement = conn.prepare(
+        "SELECT flows.id,
+             highlight(flows_fts, 0, ?3, ?4),
+             highlight(flows_fts, 1, ?3, ?4),
+             highlight(flows_fts, 2, ?3, ?4),
+             highlight(flows_fts, 3, ?3, ?4)
+         FROM flows_fts JOIN flows ON flows.rowid = flows_fts.rowid
+         WHERE flows_fts MATCH ?1 ORDER BY rank LIMIT ?2",
+    )?;
+    let rows = statement.query_map(
+        params![
+            match_expr,
+            MAX_SEARCH_RESULTS,
+            MATCH_START.to_string(),
+            MATCH_END.to_string()
+        ],
+        search_hit,
+    )?;
+    rows.collect()
+}
+
+/// Full-text search over URLs, headers and bodies of stored flows, best matches first.
+#[tauri::command]
+pub fn search_flows(
+    app: AppHandle,
+    store: State<FlowStoreState>,
+    query: String,
+) -> Result<Vec<SearchHit>, String> {
+    store.with_conn(&app, |conn| search(conn, &query))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::flow_store::{insert_record, prepare_schema};
+    use crate::ipc::FlowRecord;
+
+    fn record(id: &str, url: &str, request_body: &str) -> FlowRecord {
+        serde_json::from_value(json!({
+            "id": id,
+            "started": 0.0,
+            "ended": 0.0,
+            "duration_ms": 0,
+            "method": "POST",
+            "url": url,
+            "host": "api.example.com",
+            "path": "/",
+            "scheme": "https",
+            "status_code": 200,
+            "request_headers": [{"name": "Authorization", "value": "Bearer Token"}],
+            "response_headers": null,
+            "request_body_size": request_body.len(),
+            "response_body_size": 0,
+            "request_body": request_body,
+            "response_body": "",
+            "request_body_truncated": false,
+            "response_body_truncated": false,
+            "error": ""
+        }))
+        .unwrap()
+    }
+
+    fn spans<'a>(hit: &SearchHit, field: &str, text: &'a str) -> Vec<&'a str> {
+        hit.highlights
+            .iter()
+            .filter(|h| h.field == field)
+            .map(|h| &text[h.start..h.end])
+            .collect()
+    }
+
+    #[test]
+    fn quotes_each_term() {
+        assert_eq!(fts_query("  token   users "), "\"token\" \"users\"");
+        assert_eq!(fts_query(""), "");
+    }
+
+    #[test]
+    fn neutralizes_fts5_syntax() {
+        assert_eq!(
+            fts_query("url:admin NOT a* (x OR y)"),
+            "\"url:admin\" \"NOT\" \"a*\" \"(x\" \"OR\" \"y)\""
+        );
+        assert_eq!(fts_query("say \"hi\""), "\"say\" \"\"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn maps_markers_to_byte_offsets_of_the_unmarked_text() {
+        let ranges = marked_ranges("url", "caf\u{E000}\u{e9}s\u{E001} & \u{E000}t\u{E001}");
+        let spans: Vec<_> = ranges.iter().map(|h| (h.start, h.end)).collect();
+        assert_eq!(spans, [(3, 6), (9, 10)]);
+        assert!(marked_ranges("url", "no match").is_empty());
+    }
+
+    #[test]
+    fn highlights_the_tokens_fts5_matched() {
+        let conn = Connection::open_in_memory().unwrap();
+        prepare_schema(&conn).unwrap();
+        let url = "https://api.example.com/\u{dc}n\u{ef}code/tokens";
+        let body = "token=1&TOKEN=2&tokenized=3";
+        insert_record(&conn, &record("a", url, body)).unwrap();
+        insert_record(&conn, &record("b", "https://api.example.com/other", "")).unwrap();
+
+        let hits = search(&conn, "token unicode").unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].flow_id, "a");
+        assert_eq!(spans(&hits[0], "url", url), ["\u{dc}n\u{ef}code"]);
+        assert_eq!(spans(&hits[0], "request_body", body), ["token", "TOKEN"]);
+        let headers = "Authorization: Bearer Token\n";
+        assert_eq!(spans(&hits[0], "headers", headers), ["Token"]);
+
+        assert_eq!(search(&conn, "other").unwrap()[0].flow_id, "b");
+        assert!(search(&conn, "  ").unwrap().is_empty());
+        assert!(search(&conn, "missing").unwrap().is_empty());
+    }
+}
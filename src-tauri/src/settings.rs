@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir: PathBuf = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Unable to resolve app data folder: {err}"))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+#[tauri::command]
+pub fn save_settings(app: AppHandle, settings: Value) -> Result<(), String> {
+    let path = settings_path(&app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Unable to create {}: {err}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(&settings).map_err(|err| err.to_string())?;
+    // Write beside the target and rename so a crash never leaves a half-written file.
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, text).map_err(|err| format!("Unable to write {}: {err}", temp.display()))?;
+    fs::rename(&temp, &path).map_err(|err| format!("Unable to save {}: {err}", path.display()))
+}
+
+/// Returns the saved settings, or an empty object when nothing has been saved yet.
+#[tauri::command]
+pub fn load_settings(app: AppHandle) -> Result<Value, String> {
+    let path = settings_path(&app)?;
+    if !path.exists() {
+        return Ok(Value::Object(Default::default()));
+    }
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("Unable to read {}: {err}", path.display()))?;
+    serde_json::from_str(&text)
+        .map_err(|err| format!("{} is not valid JSON: {err}", path.display()))
+}
@@ -1,10 +1,11 @@
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
-use std::net::TcpStream;
+use std::net::{TcpListener, TcpStream};
 use std::sync::Mutex;
 use std::thread;
 use std::time::{Duration, Instant};
 
+use serde::Serialize;
 use tauri::{AppHandle, Manager, State};
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -14,6 +15,40 @@ pub struct SidecarState {
     child: Mutex<Option<Child>>,
 }
 
+impl SidecarState {
+    pub fn child_pid(&self) -> Option<u32> {
+        self.child.lock().ok()?.as_ref().map(Child::id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupCheck {
+    pub name: String,
+    pub passed: bool,
+    pub detail: String,
+    pub guidance: Option<String>,
+}
+
+impl StartupCheck {
+    fn pass(name: &str, detail: String) -> Self {
+        Self {
+            name: name.into(),
+            passed: true,
+            detail,
+            guidance: None,
+        }
+    }
+
+    fn fail(name: &str, detail: String, guidance: &str) -> Self {
+        Self {
+            name: name.into(),
+            passed: false,
+            detail,
+            guidance: Some(guidance.into()),
+        }
+    }
+}
+
 fn sidecar_script_path(app: &AppHandle) -> PathBuf {
     if let Ok(resource_dir) = app.path().resource_dir() {
         let resource_dir: PathBuf = resource_dir;
@@ -91,6 +126,143 @@ fn wait_for_ipc_ready(ipc_port: u16, timeout: Duration) -> bool {
     false
 }
 
+/// Resident memory of a process as reported by the OS, independent of the
+/// sidecar's own accounting.
+pub(crate) fn process_rss_bytes(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let pid_filter = format!("PID eq {pid}");
+        let output = Command::new("tasklist")
+            .args(["/FI", pid_filter.as_str(), "/FO", "CSV", "/NH"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        // "packetlens-sidecar.exe","1234","Console","1","45,236 K"
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mem_usage = text.trim().rsplit("\",\"").next()?;
+        let kib: u64 = mem_usage
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .ok()?;
+        return Some(kib * 1024);
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+        let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kib * 1024)
+    }
+}
+
+fn port_is_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+fn python_version() -> Option<String> {
+    let output = Command::new("python").arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Older Python releases print the version on stderr.
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    Some(String::from_utf8_lossy(&text).trim().to_string())
+}
+
+#[tauri::command]
+pub fn diagnose_startup(
+    app: AppHandle,
+    state: State<SidecarState>,
+    ipc_port: u16,
+    proxy_port: u16,
+) -> Result<Vec<StartupCheck>, String> {
+    let sidecar_running = state
+        .child
+        .lock()
+        .map_err(|_| "Sidecar lock poisoned")?
+        .is_some();
+    let binary = sidecar_binary_path(&app);
+    let mut checks = Vec::new();
+
+    checks.push(match &binary {
+        Some(path) => StartupCheck::pass("sidecar_binary", format!("Found {}", path.display())),
+        None => StartupCheck::fail(
+            "sidecar_binary",
+            "packetlens-sidecar binary not found.".into(),
+            "Rebuild the sidecar (npm run sidecar:build) or reinstall PacketLens.",
+        ),
+    });
+
+    let script = sidecar_script_path(&app);
+    checks.push(match python_version() {
+        Some(version) if script.exists() => StartupCheck::pass(
+            "python_fallback",
+            format!("{version} with {}", script.display()),
+        ),
+        Some(version) => StartupCheck::fail(
+            "python_fallback",
+            format!("{version} found but {} is missing.", script.display()),
+            "Restore sidecar/proxy_service.py or rely on the bundled sidecar binary.",
+        ),
+        None if binary.is_some() => StartupCheck::pass(
+            "python_fallback",
+            "Python not found; not needed while the sidecar binary is present.".into(),
+        ),
+        None => StartupCheck::fail(
+            "python_fallback",
+            "Python is not available on PATH.".into(),
+            "Install Python with the packages in sidecar/requirements.txt, or rebuild the sidecar binary.",
+        ),
+    });
+
+    checks.push(if sidecar_running {
+        StartupCheck::pass("ipc_port", format!("Sidecar already owns 127.0.0.1:{ipc_port}"))
+    } else if port_is_free(ipc_port) {
+        StartupCheck::pass("ipc_port", format!("127.0.0.1:{ipc_port} is free"))
+    } else {
+        StartupCheck::fail(
+            "ipc_port",
+            format!("127.0.0.1:{ipc_port} is already in use."),
+            "Close any other PacketLens instance or stale packetlens-sidecar process, then retry.",
+        )
+    });
+
+    checks.push(if port_is_free(proxy_port) {
+        StartupCheck::pass("proxy_port", format!("127.0.0.1:{proxy_port} is free"))
+    } else {
+        StartupCheck::fail(
+            "proxy_port",
+            format!("127.0.0.1:{proxy_port} is already in use."),
+            "Pick another proxy port or stop the program that owns it.",
+        )
+    });
+
+    checks.push(match crate::system::cert_path() {
+        Ok(cert) if cert.exists() => {
+            StartupCheck::pass("ca_certificate", format!("Found {}", cert.display()))
+        }
+        Ok(cert) => StartupCheck::fail(
+            "ca_certificate",
+            format!("{} does not exist yet.", cert.display()),
+            "Start Capture once so the proxy can generate the CA, then install it.",
+        ),
+        Err(err) => StartupCheck::fail(
+            "ca_certificate",
+            err,
+            "Make sure USERPROFILE or HOME is set for the current user.",
+        ),
+    });
+
+    Ok(checks)
+}
+
 #[tauri::command]
 pub fn start_sidecar(
     app: AppHandle,
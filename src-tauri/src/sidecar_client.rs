@@ -1,16 +1,108 @@
-use std::io::{BufRead, BufReader, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::net::TcpStream;
+use std::path::Path;
 use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 
-use tauri::{AppHandle, Emitter, State};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::ipc::{ProxyCommand, ProxyEvent};
+use crate::flow_store::FlowStoreState;
+use crate::ipc::{BindScope, FlowRecord, ProxyCommand, ProxyEvent, ProxyStatus};
+use crate::scripts::ScriptState;
+use crate::sidecar::{process_rss_bytes, SidecarState};
+
+/// Upper bound for a single IPC line; anything longer is dropped instead of buffered.
+const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
 
 #[derive(Default)]
 pub struct SidecarClientState {
     listener: Mutex<Option<thread::JoinHandle<()>>>,
+    ports: Mutex<BoundPorts>,
+    http3: Mutex<bool>,
+}
+
+/// Ports the sidecar reported as actually bound, which may differ from the requested ones.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct BoundPorts {
+    pub proxy_port: Option<u16>,
+    pub ipc_port: Option<u16>,
+}
+
+impl SidecarClientState {
+    pub fn bound_ports(&self) -> BoundPorts {
+        self.ports.lock().map(|ports| *ports).unwrap_or_default()
+    }
+
+    /// Whether the running proxy was started with HTTP/3 support.
+    pub fn http3_enabled(&self) -> bool {
+        self.http3.lock().map(|http3| *http3).unwrap_or(false)
+    }
+
+    fn record_status(
+        &self,
+        status: &ProxyStatus,
+        proxy_port: Option<u16>,
+        ipc_port: Option<u16>,
+        http3: Option<bool>,
+    ) {
+        if let (Some(http3), Ok(mut current)) = (http3, self.http3.lock()) {
+            *current = http3;
+        }
+        let Ok(mut ports) = self.ports.lock() else {
+            return;
+        };
+        if ipc_port.is_some() {
+            ports.ipc_port = ipc_port;
+        }
+        ports.proxy_port = match status {
+            ProxyStatus::Stopped => None,
+            _ => proxy_port.or(ports.proxy_port),
+        };
+    }
+}
+
+enum Frame {
+    Line(Vec<u8>),
+    Overflow(u64),
+    Eof,
+}
+
+fn read_frame<R: BufRead>(reader: &mut R, max_line_bytes: usize) -> io::Result<Frame> {
+    let mut line = Vec::new();
+    let read = reader
+        .by_ref()
+        .take(max_line_bytes as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(Frame::Eof);
+    }
+    if line.ends_with(b"\n") || line.len() <= max_line_bytes {
+        return Ok(Frame::Line(line));
+    }
+
+    // Discard the remainder of the oversized line without holding it in memory.
+    let mut skipped = line.len() as u64;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            break;
+        }
+        match available.iter().position(|byte| *byte == b'\n') {
+            Some(pos) => {
+                reader.consume(pos + 1);
+                skipped += pos as u64 + 1;
+                break;
+            }
+            None => {
+                let len = available.len();
+                reader.consume(len);
+                skipped += len as u64;
+            }
+        }
+    }
+    Ok(Frame::Overflow(skipped))
 }
 
 #[tauri::command]
@@ -18,19 +110,118 @@ pub fn start_sidecar_listener(
     app: AppHandle,
     state: State<SidecarClientState>,
     ipc_port: u16,
+    max_line_bytes: Option<usize>,
 ) -> Result<(), String> {
     let mut guard = state.listener.lock().map_err(|_| "Listener lock poisoned")?;
     if guard.is_some() {
         return Ok(());
     }
+    let max_line_bytes = max_line_bytes.unwrap_or(DEFAULT_MAX_LINE_BYTES).max(1);
 
     let handle = thread::spawn(move || loop {
         match TcpStream::connect(("127.0.0.1", ipc_port)) {
             Ok(stream) => {
-                let reader = BufReader::new(stream);
-                for line in reader.lines().flatten() {
-                    if let Ok(event) = serde_json::from_str::<ProxyEvent>(&line) {
-                        let _ = app.emit("proxy-event", event);
+                if let Err(err) = crate::rules::push_saved(&app, ipc_port) {
+                    log::warn!("Saved rules not sent to sidecar: {err}");
+                }
+                let mut reader = BufReader::new(stream);
+                loop {
+                    match read_frame(&mut reader, max_line_bytes) {
+                        Ok(Frame::Line(line)) => {
+                            if let Ok(mut event) = serde_json::from_slice::<ProxyEvent>(&line) {
+                                match &mut event {
+                                    ProxyEvent::Flow { record } => {
+                                        crate::graphql::enrich(record);
+                                        crate::process::enrich(
+                                            record,
+                                            app.state::<SidecarClientState>()
+                                                .bound_ports()
+                                                .proxy_port,
+                                        );
+                                        let Some(notifications) =
+                                            app.state::<ScriptState>().apply(record)
+                                        else {
+                                            continue;
+                                        };
+                                        for notification in notifications {
+                                            let _ = app.emit(
+                                                "proxy-event",
+                                                ProxyEvent::ScriptNotification {
+                                                    flow_id: record.id.clone(),
+                                                    script: notification.script,
+                                                    message: notification.message,
+                                                },
+                                            );
+                                        }
+                                        let store = app.state::<FlowStoreState>();
+                                        if let Err(err) = store.insert(&app, record) {
+                                            log::warn!("Flow {} not persisted: {err}", record.id);
+                                        }
+                                        match store.prune(&app) {
+                                            Ok(pruned) => {
+                                                for flow_id in pruned {
+                                                    let _ = app.emit(
+                                                        "proxy-event",
+                                                        ProxyEvent::FlowEvicted { flow_id },
+                                                    );
+                                                }
+                                            }
+                                            Err(err) => log::warn!("Flow store not pruned: {err}"),
+                                        }
+                                    }
+                                    ProxyEvent::FlowEvicted { flow_id } => {
+                                        if let Err(err) =
+                                            app.state::<FlowStoreState>().remove(&app, flow_id)
+                                        {
+                                            log::warn!("Flow {flow_id} not removed: {err}");
+                                        }
+                                    }
+                                    ProxyEvent::FlowPinned { flow_id, pinned } => {
+                                        if let Err(err) = app
+                                            .state::<FlowStoreState>()
+                                            .set_pinned(&app, flow_id, *pinned)
+                                        {
+                                            log::warn!("Flow {flow_id} pin not stored: {err}");
+                                        }
+                                    }
+                                    ProxyEvent::MemoryUsage { os_rss_bytes, .. } => {
+                                        *os_rss_bytes = app
+                                            .state::<SidecarState>()
+                                            .child_pid()
+                                            .and_then(process_rss_bytes);
+                                    }
+                                    ProxyEvent::Status {
+                                        status,
+                                        proxy_port,
+                                        ipc_port,
+                                        http3,
+                                        ..
+                                    } => {
+                                        app.state::<SidecarClientState>().record_status(
+                                            status,
+                                            *proxy_port,
+                                            *ipc_port,
+                                            *http3,
+                                        );
+                                    }
+                                    _ => {}
+                                }
+                                let _ = app.emit("proxy-event", event);
+                            }
+                        }
+                        Ok(Frame::Overflow(skipped_bytes)) => {
+                            log::warn!(
+                                "Dropped {skipped_bytes}-byte sidecar line (limit {max_line_bytes} bytes)"
+                            );
+                            let _ = app.emit(
+                                "proxy-event",
+                                ProxyEvent::LineOverflow {
+                                    skipped_bytes,
+                                    limit_bytes: max_line_bytes as u64,
+                                },
+                            );
+                        }
+                        Ok(Frame::Eof) | Err(_) => break,
                     }
                 }
             }
@@ -44,8 +235,106 @@ pub fn start_sidecar_listener(
     Ok(())
 }
 
+fn validate_output_path(path: &str) -> Result<(), String> {
+    let path = Path::new(path.trim());
+    if path.as_os_str().is_empty() {
+        return Err("Output path is empty.".into());
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err(format!("Output folder does not exist: {}", parent.display()));
+        }
+    }
+    Ok(())
+}
+
+fn validate_input_file(path: &str) -> Result<(), String> {
+    let path = Path::new(path.trim());
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    Ok(())
+}
+
+fn validate_command(command: &ProxyCommand) -> Result<(), String> {
+    match command {
+        ProxyCommand::ExportWaterfall { path }
+        | ProxyCommand::ExportPostman { path, .. }
+        | ProxyCommand::ExportOpenApi { path, .. }
+        | ProxyCommand::SaveResponseBody { path, .. }
+        | ProxyCommand::StartTail { path, .. }
+        | ProxyCommand::ExportRedactedHar { path, .. } => validate_output_path(path),
+        ProxyCommand::ReplaySequence { path, .. } => validate_input_file(path),
+        ProxyCommand::CompareBaseline { baseline_path } => validate_input_file(baseline_path),
+        ProxyCommand::SetProtoDescriptor { descriptor_path } => validate_input_file(descriptor_path),
+        ProxyCommand::Start {
+            bind_scope: BindScope::All,
+            confirm_lan: false,
+            ..
+        } => Err("LAN mode exposes the proxy to your network and must be confirmed.".into()),
+        ProxyCommand::Start {
+            transparent_processes,
+            ..
+        } => transparent_processes.iter().try_for_each(|process| {
+            if process.trim().is_empty() || process.contains(',') {
+                Err(format!("Invalid process for transparent capture: '{process}'"))
+            } else {
+                Ok(())
+            }
+        }),
+        ProxyCommand::ExportSessionDiff {
+            baseline_path,
+            out_path,
+        } => validate_input_file(baseline_path).and_then(|()| validate_output_path(out_path)),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_bound_ports(state: State<SidecarClientState>) -> BoundPorts {
+    state.bound_ports()
+}
+
+/// Re-sends a captured flow through the proxy; the response arrives as a new flow whose
+/// `replay_of` is `id`.
+#[tauri::command]
+pub fn replay_flow(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    id: String,
+) -> Result<(), String> {
+    send_proxy_command(state, ipc_port, ProxyCommand::ReplayFlow { id })
+}
+
+/// Replays `record` as edited in the UI. The new flow links back to the captured one through
+/// `replay_of` and lists what changed in `replay_edits`.
+#[tauri::command]
+pub fn edit_and_replay(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    record: FlowRecord,
+) -> Result<(), String> {
+    send_proxy_command(
+        state,
+        ipc_port,
+        ProxyCommand::EditAndReplay {
+            parent_id: record.id,
+            method: record.method,
+            url: record.url,
+            headers: record.request_headers,
+            body: record.request_body,
+        },
+    )
+}
+
 #[tauri::command]
-pub fn send_proxy_command(ipc_port: u16, command: ProxyCommand) -> Result<(), String> {
+pub fn send_proxy_command(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    command: ProxyCommand,
+) -> Result<(), String> {
+    validate_command(&command)?;
+    let ipc_port = state.bound_ports().ipc_port.unwrap_or(ipc_port);
     let payload =
         serde_json::to_string(&command).map_err(|e| format!("Serialize failed: {e}"))?;
     let mut last_error = String::new();
@@ -71,3 +360,42 @@ pub fn send_proxy_command(ipc_port: u16, command: ProxyCommand) -> Result<(), St
         "Connect failed after retries to 127.0.0.1:{ipc_port}: {last_error}"
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(input: &[u8], max_line_bytes: usize) -> Vec<String> {
+        // A tiny buffer makes the overflow path span several fill_buf calls.
+        let mut reader = BufReader::with_capacity(2, input);
+        let mut frames = Vec::new();
+        loop {
+            match read_frame(&mut reader, max_line_bytes).unwrap() {
+                Frame::Line(line) => frames.push(String::from_utf8(line).unwrap()),
+                Frame::Overflow(skipped) => frames.push(format!("overflow {skipped}")),
+                Frame::Eof => return frames,
+            }
+        }
+    }
+
+    #[test]
+    fn reads_lines_up_to_the_limit() {
+        assert_eq!(frames(b"abc\nabcd\n", 4), ["abc\n", "abcd\n"]);
+        assert_eq!(frames(b"abcd", 4), ["abcd"]);
+        assert!(frames(b"", 4).is_empty());
+    }
+
+    #[test]
+    fn skips_oversized_lines_and_resumes_at_the_next_one() {
+        assert_eq!(frames(b"abcdefgh\nok\n", 4), ["overflow 9", "ok\n"]);
+        assert_eq!(
+            frames(b"ok\n0123456789\n\nlast", 4),
+            ["ok\n", "overflow 11", "\n", "last"]
+        );
+    }
+
+    #[test]
+    fn counts_an_unterminated_oversized_line_up_to_eof() {
+        assert_eq!(frames(b"abcdefg", 4), ["overflow 7"]);
+    }
+}
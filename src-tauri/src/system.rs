@@ -1,10 +1,28 @@
 use std::env;
+use std::fs;
+use std::io;
 use std::net::TcpStream;
 use std::path::PathBuf;
 use std::process::Command;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_fs::{FsExt, OpenOptions};
+
+use crate::ipc::ProxyCommand;
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+#[cfg(target_os = "windows")]
+const SUPPORTED_BROWSERS: [&str; 4] = ["edge", "chrome", "firefox", "brave"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedBrowser {
+    pub id: String,
+    pub path: String,
+}
+
 fn home_dir() -> Result<PathBuf, String> {
     if let Ok(path) = env::var("USERPROFILE") {
         return Ok(PathBuf::from(path));
@@ -19,10 +37,25 @@ fn cert_dir() -> Result<PathBuf, String> {
     Ok(home_dir()?.join(".mitmproxy"))
 }
 
-fn cert_path() -> Result<PathBuf, String> {
+pub(crate) fn cert_path() -> Result<PathBuf, String> {
     Ok(cert_dir()?.join("mitmproxy-ca-cert.cer"))
 }
 
+fn pem_cert_path() -> Result<PathBuf, String> {
+    Ok(cert_dir()?.join("mitmproxy-ca-cert.pem"))
+}
+
+fn existing_cert_path() -> Result<PathBuf, String> {
+    let cert = cert_path()?;
+    if !cert.exists() {
+        return Err(format!(
+            "CA certificate not found at {}. Start Capture once so the proxy can generate it, then retry.",
+            cert.display()
+        ));
+    }
+    Ok(cert)
+}
+
 #[cfg(target_os = "windows")]
 fn browser_candidates(browser: &str) -> Vec<PathBuf> {
     let local_app_data = env::var("LOCALAPPDATA").unwrap_or_default();
@@ -61,19 +94,23 @@ fn browser_candidates(browser: &str) -> Vec<PathBuf> {
 fn resolve_browser_exe(browser: &str) -> Result<PathBuf, String> {
     let normalized = browser.trim().to_lowercase();
 
-    if normalized == "edge" || normalized == "chrome" || normalized == "brave" || normalized == "firefox" {
-        for candidate in browser_candidates(&normalized) {
-            if candidate.exists() {
-                return Ok(candidate);
-            }
-        }
-        return Err(format!("Requested browser '{normalized}' was not found on this PC."));
+    if SUPPORTED_BROWSERS.contains(&normalized.as_str()) {
+        return find_browser_exe(&normalized)
+            .ok_or_else(|| format!("Requested browser '{normalized}' was not found on this PC."));
     }
     Err(format!(
-        "Unsupported browser '{browser}'. Choose one of: edge, chrome, firefox, brave."
+        "Unsupported browser '{browser}'. Choose one of: {}.",
+        SUPPORTED_BROWSERS.join(", ")
     ))
 }
 
+#[cfg(target_os = "windows")]
+fn find_browser_exe(browser: &str) -> Option<PathBuf> {
+    browser_candidates(browser)
+        .into_iter()
+        .find(|candidate| candidate.exists())
+}
+
 #[cfg(target_os = "windows")]
 fn wait_for_proxy_port(port: u16, timeout: Duration) -> bool {
     let start = Instant::now();
@@ -105,7 +142,7 @@ pub fn open_cert_folder() -> Result<(), String> {
 
 #[tauri::command]
 pub fn install_cert() -> Result<(), String> {
-    let cert = cert_path()?;
+    let cert = existing_cert_path()?;
     #[cfg(target_os = "windows")]
     {
         let result = Command::new("certutil")
@@ -124,6 +161,49 @@ pub fn install_cert() -> Result<(), String> {
     }
 }
 
+/// Writes the CA certificate as `.cer` and `.pem` next to `dest_path`. Both files must be
+/// inside the fs plugin's scope; a save dialog only adds the file it picked, so the folder
+/// has to be in scope to export both.
+#[tauri::command]
+pub fn export_cert(app: AppHandle, dest_path: String) -> Result<Vec<String>, String> {
+    let cert = existing_cert_path()?;
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("Export path is empty.".into());
+    }
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if !parent.is_dir() {
+            return Err(format!("Export folder does not exist: {}", parent.display()));
+        }
+    }
+
+    // mitmproxy writes the .cer in PEM encoding, so it doubles as the .pem source
+    // when the dedicated .pem file is missing.
+    let pem_source = pem_cert_path()?;
+    let pem_source = if pem_source.exists() { pem_source } else { cert.clone() };
+
+    let exports = [(cert, "cer"), (pem_source, "pem")].map(|(source, extension)| {
+        (source, dest.with_extension(extension))
+    });
+    if let Some((_, target)) = exports
+        .iter()
+        .find(|(_, target)| !app.fs_scope().is_allowed(target))
+    {
+        return Err(format!("Export path is not allowed: {}", target.display()));
+    }
+
+    let mut written = Vec::new();
+    for (source, target) in exports {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        fs::File::open(&source)
+            .and_then(|mut reader| io::copy(&mut reader, &mut app.fs().open(&target, options)?))
+            .map_err(|err| format!("Failed to export cert to {}: {err}", target.display()))?;
+        written.push(target.display().to_string());
+    }
+    Ok(written)
+}
+
 #[tauri::command]
 pub fn uninstall_cert() -> Result<(), String> {
     #[cfg(target_os = "windows")]
@@ -144,7 +224,34 @@ pub fn uninstall_cert() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn open_browser(port: u16, browser: String) -> Result<(), String> {
+pub fn list_browsers() -> Result<Vec<DetectedBrowser>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        return Ok(SUPPORTED_BROWSERS
+            .iter()
+            .filter_map(|id| {
+                find_browser_exe(id).map(|path| DetectedBrowser {
+                    id: id.to_string(),
+                    path: path.display().to_string(),
+                })
+            })
+            .collect());
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("PacketLens is supported on Windows only.".into())
+    }
+}
+
+/// Returns a correlation token (the temp profile's directory name) for `ScopeToClient`.
+#[tauri::command]
+pub fn open_browser(
+    client: State<SidecarClientState>,
+    port: u16,
+    browser: String,
+) -> Result<String, String> {
+    // Prefer the port the sidecar actually bound over the one the UI asked for.
+    let port = client.bound_ports().proxy_port.unwrap_or(port);
     #[cfg(target_os = "windows")]
     {
         let browser_exe = resolve_browser_exe(&browser)?;
@@ -157,13 +264,20 @@ pub fn open_browser(port: u16, browser: String) -> Result<(), String> {
             .duration_since(UNIX_EPOCH)
             .map_err(|e| format!("Clock error: {e}"))?
             .as_millis();
-        let profile_dir = env::temp_dir().join(format!("packetlens-browser-profile-{profile_id}"));
+        let token = format!("packetlens-browser-profile-{profile_id}");
+        let profile_dir = env::temp_dir().join(&token);
+        // Keep the browser on TCP unless the proxy was started with HTTP/3 support.
+        let quic_flag = if client.http3_enabled() {
+            "--enable-quic"
+        } else {
+            "--disable-quic"
+        };
 
         Command::new(browser_exe)
             .args([
                 format!("--proxy-server=127.0.0.1:{port}"),
                 "--proxy-bypass-list=localhost;127.0.0.1;::1".to_string(),
-                "--disable-quic".to_string(),
+                quic_flag.to_string(),
                 format!("--user-data-dir={}", profile_dir.display()),
                 "--no-first-run".to_string(),
                 "--new-window".to_string(),
@@ -171,10 +285,325 @@ pub fn open_browser(port: u16, browser: String) -> Result<(), String> {
             ])
             .spawn()
             .map_err(|err| format!("Failed to open browser with proxy: {err}"))?;
+        return Ok(token);
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("PacketLens is supported on Windows only.".into())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InterceptionCheck {
+    pub url: String,
+    pub handshake_ok: bool,
+    pub intercepted: bool,
+    pub issuer: Option<String>,
+    pub detail: String,
+}
+
+#[cfg(target_os = "windows")]
+const INTERCEPTION_TEST_URL: &str = "https://example.com/";
+
+/// Fetches a known HTTPS page through the proxy using the Windows trust store, so a pass
+/// means the CA is installed, trusted, and actually presented by the proxy.
+#[tauri::command]
+pub fn verify_interception(
+    client: State<SidecarClientState>,
+    proxy_port: u16,
+) -> Result<InterceptionCheck, String> {
+    let port = client.bound_ports().proxy_port.unwrap_or(proxy_port);
+    #[cfg(target_os = "windows")]
+    {
+        if !wait_for_proxy_port(port, Duration::from_secs(5)) {
+            return Err(format!(
+                "Proxy is not ready on 127.0.0.1:{port}. Click Start Capture, wait for Running status, then retry."
+            ));
+        }
+        // HTTP error statuses still mean the handshake worked; only transport failures count.
+        let script = format!(
+            "$req = [System.Net.HttpWebRequest]::Create('{INTERCEPTION_TEST_URL}'); \
+             $req.Proxy = New-Object System.Net.WebProxy('http://127.0.0.1:{port}'); \
+             $req.Timeout = 15000; $status = 'ok'; \
+             try {{ $req.GetResponse().Close() }} catch [System.Net.WebException] {{ \
+               if ($_.Exception.Response) {{ $_.Exception.Response.Close() }} \
+               else {{ $status = [string]$_.Exception.Status }} }}; \
+             Write-Output $status; \
+             if ($req.ServicePoint.Certificate) {{ Write-Output $req.ServicePoint.Certificate.Issuer }}"
+        );
+        let result = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+            .map_err(|err| format!("Failed to run powershell: {err}"))?;
+        if !result.status.success() {
+            return Err(String::from_utf8_lossy(&result.stderr).to_string());
+        }
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        let mut lines = stdout.lines().map(str::trim).filter(|line| !line.is_empty());
+        let status = lines.next().unwrap_or("unknown").to_string();
+        let issuer = lines.next().map(str::to_string);
+        let handshake_ok = status == "ok";
+        let intercepted = issuer
+            .as_deref()
+            .is_some_and(|issuer| issuer.to_lowercase().contains("mitmproxy"));
+        let detail = match (handshake_ok, intercepted) {
+            (true, true) => "TLS through the proxy succeeded with the PacketLens CA in the chain.".to_string(),
+            (true, false) => format!(
+                "TLS succeeded but the certificate was issued by {}; the proxy did not intercept it.",
+                issuer.as_deref().unwrap_or("an unknown issuer")
+            ),
+            _ if status == "TrustFailure" => {
+                "The proxy's certificate is not trusted. Install the CA certificate and retry.".to_string()
+            }
+            _ => format!("Request through the proxy failed: {status}"),
+        };
+        return Ok(InterceptionCheck {
+            url: INTERCEPTION_TEST_URL.to_string(),
+            handshake_ok,
+            intercepted: handshake_ok && intercepted,
+            issuer,
+            detail,
+        });
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = port;
+        Err("PacketLens is supported on Windows only.".into())
+    }
+}
+
+#[cfg(target_os = "windows")]
+const INTERNET_SETTINGS_KEY: &str =
+    r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings";
+
+const SYSTEM_PROXY_BACKUP_FILE: &str = "system_proxy_backup.json";
+
+/// The user's WinINET proxy values from before PacketLens changed them; `None` means the
+/// value was absent and is deleted again on restore.
+#[cfg(target_os = "windows")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SystemProxyBackup {
+    proxy_enable: Option<u32>,
+    proxy_server: Option<String>,
+    proxy_override: Option<String>,
+    auto_config_url: Option<String>,
+}
+
+fn system_proxy_backup_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir: PathBuf = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Unable to resolve app data folder: {err}"))?;
+    fs::create_dir_all(&dir).map_err(|err| format!("Unable to create {}: {err}", dir.display()))?;
+    Ok(dir.join(SYSTEM_PROXY_BACKUP_FILE))
+}
+
+#[cfg(target_os = "windows")]
+fn reg_query(name: &str) -> Option<String> {
+    let output = Command::new("reg")
+        .args(["query", INTERNET_SETTINGS_KEY, "/v", name])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // "    ProxyServer    REG_SZ    127.0.0.1:8080"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().find_map(|line| {
+        let mut parts = line.trim().splitn(3, "    ");
+        if parts.next()? != name {
+            return None;
+        }
+        parts.next()?;
+        Some(parts.next().unwrap_or("").trim().to_string())
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn reg_set(name: &str, kind: &str, value: &str) -> Result<(), String> {
+    let result = Command::new("reg")
+        .args([
+            "add",
+            INTERNET_SETTINGS_KEY,
+            "/v",
+            name,
+            "/t",
+            kind,
+            "/d",
+            value,
+            "/f",
+        ])
+        .output()
+        .map_err(|err| format!("Failed to run reg: {err}"))?;
+    if !result.status.success() {
+        return Err(String::from_utf8_lossy(&result.stderr).to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn reg_delete(name: &str) {
+    // A missing value is already the desired state, so failures are ignored.
+    let _ = Command::new("reg")
+        .args(["delete", INTERNET_SETTINGS_KEY, "/v", name, "/f"])
+        .output();
+}
+
+#[cfg(target_os = "windows")]
+fn reg_restore(name: &str, kind: &str, value: Option<String>) -> Result<(), String> {
+    match value {
+        Some(value) => reg_set(name, kind, &value),
+        None => {
+            reg_delete(name);
+            Ok(())
+        }
+    }
+}
+
+/// Tells running WinINET clients to reload the proxy settings from the registry.
+#[cfg(target_os = "windows")]
+fn refresh_internet_settings() {
+    use windows_sys::Win32::Networking::WinInet::{
+        InternetSetOptionW, INTERNET_OPTION_REFRESH, INTERNET_OPTION_SETTINGS_CHANGED,
+    };
+
+    unsafe {
+        InternetSetOptionW(
+            std::ptr::null(),
+            INTERNET_OPTION_SETTINGS_CHANGED,
+            std::ptr::null(),
+            0,
+        );
+        InternetSetOptionW(
+            std::ptr::null(),
+            INTERNET_OPTION_REFRESH,
+            std::ptr::null(),
+            0,
+        );
+    }
+}
+
+/// Saves the current settings unless a backup from an earlier enable is still pending, so
+/// re-enabling never overwrites the user's original values with PacketLens's own.
+#[cfg(target_os = "windows")]
+fn backup_system_proxy(app: &AppHandle) -> Result<(), String> {
+    let path = system_proxy_backup_path(app)?;
+    if path.exists() {
+        return Ok(());
+    }
+    let backup = SystemProxyBackup {
+        proxy_enable: reg_query("ProxyEnable")
+            .and_then(|value| u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()),
+        proxy_server: reg_query("ProxyServer"),
+        proxy_override: reg_query("ProxyOverride"),
+        auto_config_url: reg_query("AutoConfigURL"),
+    };
+    let text = serde_json::to_string_pretty(&backup).map_err(|err| err.to_string())?;
+    fs::write(&path, text).map_err(|err| format!("Unable to write {}: {err}", path.display()))
+}
+
+/// Points the per-user Windows proxy at PacketLens so apps that honor the system proxy are
+/// captured. The previous settings are kept for `disable_system_proxy`.
+#[tauri::command]
+pub fn enable_system_proxy(
+    app: AppHandle,
+    client: State<SidecarClientState>,
+    port: u16,
+) -> Result<(), String> {
+    let port = client.bound_ports().proxy_port.unwrap_or(port);
+    #[cfg(target_os = "windows")]
+    {
+        backup_system_proxy(&app)?;
+        // Automatic configuration takes precedence over a manual proxy.
+        reg_delete("AutoConfigURL");
+        reg_set("ProxyServer", "REG_SZ", &format!("127.0.0.1:{port}"))?;
+        reg_set("ProxyOverride", "REG_SZ", "localhost;127.*;[::1];<local>")?;
+        reg_set("ProxyEnable", "REG_DWORD", "1")?;
+        refresh_internet_settings();
+        return Ok(());
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (app, port);
+        Err("PacketLens is supported on Windows only.".into())
+    }
+}
+
+/// Starts the sidecar's PAC server with the given host rules and makes it the per-user
+/// automatic proxy configuration, so only matching hosts go through PacketLens. An empty
+/// `include` proxies every host that is not excluded.
+#[tauri::command]
+pub fn enable_pac_system_proxy(
+    app: AppHandle,
+    client: State<SidecarClientState>,
+    ipc_port: u16,
+    pac_port: u16,
+    include: Vec<String>,
+    exclude: Vec<String>,
+) -> Result<String, String> {
+    if pac_port == 0 {
+        return Err("Choose a port for the PAC server.".into());
+    }
+    send_proxy_command(
+        client,
+        ipc_port,
+        ProxyCommand::StartPacServer {
+            port: pac_port,
+            include,
+            exclude,
+        },
+    )?;
+    let url = format!("http://127.0.0.1:{pac_port}/proxy.pac");
+    #[cfg(target_os = "windows")]
+    {
+        // WinINET caches PAC files by URL, so a fresh query string forces a reload.
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("Clock error: {e}"))?
+            .as_millis();
+        backup_system_proxy(&app)?;
+        reg_set("AutoConfigURL", "REG_SZ", &format!("{url}?v={stamp}"))?;
+        reg_set("ProxyEnable", "REG_DWORD", "0")?;
+        refresh_internet_settings();
+        return Ok(url);
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (app, url);
+        Err("PacketLens is supported on Windows only.".into())
+    }
+}
+
+/// Restores the proxy settings saved by `enable_system_proxy` or `enable_pac_system_proxy`,
+/// or just turns the manual proxy off when nothing was saved.
+#[tauri::command]
+pub fn disable_system_proxy(app: AppHandle) -> Result<(), String> {
+    let path = system_proxy_backup_path(&app)?;
+    #[cfg(target_os = "windows")]
+    {
+        let backup: SystemProxyBackup = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text)
+                .map_err(|err| format!("{} is not valid JSON: {err}", path.display()))?,
+            Err(_) => SystemProxyBackup::default(),
+        };
+        reg_set(
+            "ProxyEnable",
+            "REG_DWORD",
+            &backup.proxy_enable.unwrap_or(0).to_string(),
+        )?;
+        if path.exists() {
+            reg_restore("ProxyServer", "REG_SZ", backup.proxy_server)?;
+            reg_restore("ProxyOverride", "REG_SZ", backup.proxy_override)?;
+            reg_restore("AutoConfigURL", "REG_SZ", backup.auto_config_url)?;
+            fs::remove_file(&path)
+                .map_err(|err| format!("Unable to remove {}: {err}", path.display()))?;
+        }
+        refresh_internet_settings();
         return Ok(());
     }
     #[cfg(not(target_os = "windows"))]
     {
+        let _ = path;
         Err("PacketLens is supported on Windows only.".into())
     }
 }
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use tauri::State;
+
+use crate::ipc::ProxyCommand;
+use crate::sidecar_client::{send_proxy_command, SidecarClientState};
+
+/// Named network profiles for `set_network_preset`; `Off` removes the throttle.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum NetworkPreset {
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "3g")]
+    ThreeG,
+    #[serde(rename = "4g")]
+    FourG,
+    #[serde(rename = "dsl")]
+    Dsl,
+}
+
+impl NetworkPreset {
+    /// (download kbps, upload kbps, latency ms)
+    fn limits(self) -> (u32, u32, u32) {
+        match self {
+            NetworkPreset::Off => (0, 0, 0),
+            NetworkPreset::ThreeG => (1_600, 768, 300),
+            NetworkPreset::FourG => (12_000, 6_000, 70),
+            NetworkPreset::Dsl => (8_000, 1_000, 20),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn set_network_preset(
+    state: State<SidecarClientState>,
+    ipc_port: u16,
+    preset: NetworkPreset,
+) -> Result<(), String> {
+    let (download_kbps, upload_kbps, latency_ms) = preset.limits();
+    send_proxy_command(
+        state,
+        ipc_port,
+        ProxyCommand::SetThrottle {
+            download_kbps,
+            upload_kbps,
+            latency_ms,
+        },
+    )
+}
@@ -3,7 +3,14 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open, save } from "@tauri-apps/plugin-dialog";
 import { readTextFile, writeTextFile } from "@tauri-apps/plugin-fs";
-import type { FlowRecord, ProxyEvent, ProxyCommand, ProxyStatus } from "./ipc/schema";
+import type {
+  CaptureSummaryEvent,
+  FlowRecord,
+  ProxyEvent,
+  ProxyCommand,
+  ProxyStatus,
+  SidecarInfoEvent,
+} from "./ipc/schema";
 import "./App.css";
 import appIcon from "../packetlens-icon-preview.svg";
 
@@ -11,9 +18,27 @@ const IPC_PORT = 8787;
 const MAX_ROWS = 5000;
 type ColumnKey = "time" | "method" | "host" | "path" | "status" | "size" | "duration";
 type BrowserTarget = "edge" | "chrome" | "firefox" | "brave";
+type DetectedBrowser = { id: BrowserTarget; path: string };
+type InterceptionCheck = {
+  url: string;
+  handshake_ok: boolean;
+  intercepted: boolean;
+  issuer: string | null;
+  detail: string;
+};
+
+const BROWSER_LABELS: Record<BrowserTarget, string> = {
+  edge: "Edge",
+  chrome: "Chrome",
+  firefox: "Firefox",
+  brave: "Brave",
+};
 
 const formatDuration = (ms: number) => `${ms} ms`;
 
+const isProxyActive = (status: ProxyStatus) =>
+  status === "running" || status === "degraded" || status === "record_only";
+
 const getHeader = (headers: FlowRecord["response_headers"], name: string) => {
   if (!headers) {
     return "";
@@ -182,12 +207,20 @@ function App() {
   const [proxyState, setProxyState] = useState<ProxyStatus>("stopped");
   const [activeProxyPort, setActiveProxyPort] = useState<number | null>(null);
   const [port, setPort] = useState("8192");
+  const [http3, setHttp3] = useState(false);
+  const [lanMode, setLanMode] = useState(false);
+  const [lanAddresses, setLanAddresses] = useState<string[]>([]);
+  const [transparentProcesses, setTransparentProcesses] = useState("");
+  const [systemProxy, setSystemProxy] = useState(false);
   const [paused, setPaused] = useState(false);
   const [autoScroll, setAutoScroll] = useState(true);
   const [browserTarget, setBrowserTarget] = useState<BrowserTarget>("edge");
+  const [installedBrowsers, setInstalledBrowsers] = useState<BrowserTarget[] | null>(null);
   const [autoOpenBrowserPending, setAutoOpenBrowserPending] = useState(false);
   const [tablePanelHeight, setTablePanelHeight] = useState<number | null>(null);
   const [showAbout, setShowAbout] = useState(false);
+  const [sidecarInfo, setSidecarInfo] = useState<SidecarInfoEvent | null>(null);
+  const [captureSummary, setCaptureSummary] = useState<CaptureSummaryEvent | null>(null);
   const contentSplitRef = useRef<HTMLDivElement | null>(null);
   const tableScrollRef = useRef<HTMLDivElement | null>(null);
   const resizeStateRef = useRef<{ col: ColumnKey; startX: number; startWidth: number } | null>(null);
@@ -202,7 +235,7 @@ function App() {
     () => records.find((record) => record.id === selectedId) ?? records[0],
     [records, selectedId],
   );
-  const isCaptureOn = proxyState === "running";
+  const isCaptureOn = isProxyActive(proxyState);
 
   useEffect(() => {
     proxyStateRef.current = proxyState;
@@ -216,6 +249,16 @@ function App() {
     browserTargetRef.current = browserTarget;
   }, [browserTarget]);
 
+  useEffect(() => {
+    invoke<DetectedBrowser[]>("list_browsers")
+      .then((detected) => {
+        const ids = detected.map((browser) => browser.id);
+        setInstalledBrowsers(ids);
+        setBrowserTarget((current) => (ids.length === 0 || ids.includes(current) ? current : ids[0]));
+      })
+      .catch(() => setInstalledBrowsers(null));
+  }, []);
+
   useEffect(() => {
     activeProxyPortRef.current = activeProxyPort;
     portRef.current = port;
@@ -239,6 +282,22 @@ function App() {
           return next.slice(-MAX_ROWS);
         });
         setSelectedId((prev) => prev || payload.record.id);
+      } else if (payload.type === "sidecar_info") {
+        setSidecarInfo(payload);
+      } else if (payload.type === "lan_addresses") {
+        setLanAddresses(payload.addresses);
+      } else if (payload.type === "capture_summary") {
+        setCaptureSummary(payload);
+      } else if (payload.type === "script_notification") {
+        setStatusText(`${payload.script}: ${payload.message}`);
+      } else if (payload.type === "flow_evicted") {
+        setRecords((prev) => prev.filter((record) => record.id !== payload.flow_id));
+      } else if (payload.type === "flow_pinned") {
+        setRecords((prev) =>
+          prev.map((record) =>
+            record.id === payload.flow_id ? { ...record, pinned: payload.pinned } : record,
+          ),
+        );
       } else if (payload.type === "status") {
         // Ignore transient "Ready" status that can arrive while startup is in progress.
         const isTransientReady =
@@ -248,14 +307,14 @@ function App() {
         if (isTransientReady) {
           return;
         }
-        setStatusText(payload.message ?? payload.status);
+        setStatusText(`${payload.message ?? payload.status}${payload.http3 ? " (HTTP/3)" : ""}`);
         setProxyState(payload.status);
         setPaused(payload.status === "paused");
         if (payload.port) {
           setActiveProxyPort(payload.port);
           setPort(String(payload.port));
         }
-        if (autoOpenPendingRef.current && payload.status === "running") {
+        if (autoOpenPendingRef.current && isProxyActive(payload.status)) {
           autoOpenPendingRef.current = false;
           setAutoOpenBrowserPending(false);
           window.setTimeout(() => {
@@ -445,11 +504,26 @@ function App() {
     setProxyState("starting");
     setStatusText(`Starting on ${requestedPort}...`);
     setAutoOpenBrowserPending(true);
-    await sendCommand({ type: "start", port: requestedPort });
+    await sendCommand({
+      type: "start",
+      port: requestedPort,
+      auto_port: true,
+      ...(lanMode ? { bind_scope: "all" as const, confirm_lan: true } : {}),
+      http3,
+      transparent_processes: transparentProcesses
+        .split(",")
+        .map((name) => name.trim())
+        .filter(Boolean),
+    });
+  };
+
+  const openAbout = () => {
+    setShowAbout(true);
+    void sendCommand({ type: "get_sidecar_info" }).catch(() => setSidecarInfo(null));
   };
 
   const startCapture = async () => {
-    if (proxyState === "starting" || proxyState === "running") {
+    if (proxyState === "starting" || isProxyActive(proxyState)) {
       return;
     }
     try {
@@ -475,7 +549,7 @@ function App() {
   };
 
   const togglePause = async () => {
-    if (proxyState !== "running" && proxyState !== "paused") {
+    if (!isProxyActive(proxyState) && proxyState !== "paused") {
       return;
     }
     if (paused) {
@@ -508,7 +582,7 @@ function App() {
           request: {
             method: record.method,
             url: record.url,
-            httpVersion: "HTTP/1.1",
+            httpVersion: record.http_version ?? "HTTP/1.1",
             headers: record.request_headers,
             queryString: [],
             headersSize: -1,
@@ -518,12 +592,17 @@ function App() {
           response: {
             status: record.status_code,
             statusText: record.error || "",
-            httpVersion: "HTTP/1.1",
+            httpVersion: record.http_version ?? "HTTP/1.1",
             headers: record.response_headers ?? [],
             headersSize: -1,
             bodySize: record.response_body_size,
-            content: { size: record.response_body_size, mimeType: "", text: record.response_body },
+            content: {
+              size: record.response_body_decoded_size ?? record.response_body_size,
+              mimeType: "",
+              text: record.response_body,
+            },
           },
+          _clientOutcome: record.client_outcome ?? undefined,
         })),
       },
     };
@@ -535,41 +614,24 @@ function App() {
     if (!file || Array.isArray(file)) {
       return;
     }
-    const data = await readTextFile(file);
     if (file.toLowerCase().endsWith(".har")) {
-      const parsed = JSON.parse(data);
-      const entries = parsed?.log?.entries ?? [];
-      const loaded = entries.map((entry: any, idx: number) => ({
-        id: `${idx}-${entry.startedDateTime}`,
-        started: Date.parse(entry.startedDateTime) / 1000,
-        ended: Date.parse(entry.startedDateTime) / 1000,
-        duration_ms: entry.time ?? 0,
-        method: entry.request?.method ?? "",
-        url: entry.request?.url ?? "",
-        host: new URL(entry.request?.url ?? "http://localhost").host,
-        path: new URL(entry.request?.url ?? "http://localhost").pathname,
-        scheme: new URL(entry.request?.url ?? "http://localhost").protocol.replace(":", ""),
-        status_code: entry.response?.status ?? 0,
-        request_headers: entry.request?.headers ?? [],
-        response_headers: entry.response?.headers ?? [],
-        request_body_size: entry.request?.bodySize ?? 0,
-        response_body_size: entry.response?.bodySize ?? 0,
-        request_body: entry.request?.postData?.text ?? "",
-        response_body: entry.response?.content?.text ?? "",
-        request_body_truncated: false,
-        response_body_truncated: false,
-        error: "",
-      })) as FlowRecord[];
-      setRecords(loaded);
+      try {
+        setRecords(await invoke<FlowRecord[]>("import_har", { path: file }));
+      } catch (error) {
+        window.alert(String(error));
+      }
     } else {
-      const loaded = JSON.parse(data) as FlowRecord[];
+      const loaded = JSON.parse(await readTextFile(file)) as FlowRecord[];
       setRecords(loaded);
     }
   };
 
   const handleClear = () => {
-    setRecords([]);
+    setRecords((prev) => prev.filter((record) => record.pinned));
     setSelectedId("");
+    if (proxyState !== "stopped") {
+      void sendCommand({ type: "clear" }).catch((error) => window.alert(String(error)));
+    }
   };
 
   const handleOpenCertFolder = async () => {
@@ -596,13 +658,28 @@ function App() {
     }
   };
 
+  const handleVerifyInterception = async () => {
+    if (!isProxyActive(proxyState)) {
+      window.alert("Capture is not running yet. Click Start and wait for Running status.");
+      return;
+    }
+    try {
+      const check = await invoke<InterceptionCheck>("verify_interception", {
+        proxyPort: activeProxyPort ?? (Number(port) || 8192),
+      });
+      window.alert(check.detail);
+    } catch (error) {
+      window.alert(String(error));
+    }
+  };
+
   const handleOpenBrowser = async () => {
     try {
       if (proxyState === "starting") {
         window.alert("Proxy is still starting. Please wait a moment and try again.");
         return;
       }
-      if (proxyState !== "running") {
+      if (!isProxyActive(proxyState)) {
         window.alert("Capture is not running yet. Click Start and wait for Running status.");
         return;
       }
@@ -614,6 +691,66 @@ function App() {
     }
   };
 
+  const handleLanMode = async (enabled: boolean) => {
+    if (!enabled) {
+      setLanMode(false);
+      return;
+    }
+    const confirmed = window.confirm(
+      "LAN mode listens on all network interfaces, so any device on your network can send traffic through PacketLens. Continue?"
+    );
+    if (!confirmed) {
+      return;
+    }
+    setLanMode(true);
+    setLanAddresses([]);
+    try {
+      await sendCommand({ type: "get_lan_addresses" });
+    } catch (error) {
+      window.alert(String(error));
+    }
+  };
+
+  const handleSystemProxy = async () => {
+    try {
+      if (systemProxy) {
+        await invoke("disable_system_proxy");
+        setSystemProxy(false);
+        return;
+      }
+      if (!isProxyActive(proxyState)) {
+        window.alert("Capture is not running yet. Click Start and wait for Running status.");
+        return;
+      }
+      await invoke("enable_system_proxy", { port: activeProxyPort ?? (Number(port) || 8192) });
+      setSystemProxy(true);
+    } catch (error) {
+      window.alert(String(error));
+    }
+  };
+
+  const handleReplay = async () => {
+    if (!selected) {
+      return;
+    }
+    try {
+      await invoke("replay_flow", { ipcPort: IPC_PORT, id: selected.id });
+    } catch (error) {
+      window.alert(String(error));
+    }
+  };
+
+  const handleCopyClientHello = async () => {
+    if (!selected?.client_hello_b64) {
+      return;
+    }
+    try {
+      await navigator.clipboard.writeText(selected.client_hello_b64);
+    } catch (error) {
+      window.alert(String(error));
+    }
+  };
+
   const handleHowToUse = () => {
     window.alert("Use Start Capture to begin capture. Set filters, click rows to inspect details.");
   };
@@ -637,6 +774,7 @@ function App() {
 
   const selectedBody = detailsTab === "request" ? selected?.request_body ?? "" : selected?.response_body ?? "";
   const selectedHeaders = detailsTab === "request" ? selected?.request_headers ?? [] : selected?.response_headers ?? [];
+  const selectedTrailers = detailsTab === "response" ? selected?.response_trailers ?? [] : [];
   const selectedJson = prettyJson(selectedBody);
   const selectedJsonTree = tryParseJson(selectedBody);
   const rawDetails = selected
@@ -644,16 +782,85 @@ function App() {
       ? [
           `${selected.method} ${selected.url}`,
           `Host: ${selected.host}`,
+          ...(selected.host_header && selected.host_header !== selected.host
+            ? [`Host Header: ${selected.host_header}`]
+            : []),
           `Scheme: ${selected.scheme}`,
+          ...(selected.http_version ? [`HTTP Version: ${selected.http_version}`] : []),
+          ...(selected.http_protocol && selected.http_protocol !== "h1"
+            ? [`Protocol: ${selected.http_protocol}${selected.stream_id != null ? ` stream ${selected.stream_id}` : ""}`]
+            : []),
+          ...(selected.pseudo_headers ?? []).map((h) => `${h.name}: ${h.value}`),
+          ...(selected.process
+            ? [
+                `Process: ${selected.process.name ?? "?"} (PID ${selected.process.pid})${
+                  selected.process.exe ? ` ${selected.process.exe}` : ""
+                }`,
+              ]
+            : []),
+          ...(selected.server_conn_id
+            ? [`Connection: client ${selected.client_conn_id ?? "-"} server ${selected.server_conn_id}`]
+            : []),
+          ...(selected.timings
+            ? [
+                `Timings: ${(
+                  [
+                    ["DNS", selected.timings.dns_ms],
+                    ["Connect", selected.timings.connect_ms],
+                    ["TLS", selected.timings.tls_ms],
+                    ["Send", selected.timings.send_ms],
+                    ["TTFB", selected.timings.ttfb_ms],
+                    ["Download", selected.timings.download_ms],
+                  ] as const
+                )
+                  .filter(([, value]) => value != null)
+                  .map(([label, value]) => `${label} ${value} ms`)
+                  .join(", ")}`,
+              ]
+            : []),
           `Request Body Size: ${selected.request_body_size}`,
           `Request Body Truncated: ${selected.request_body_truncated}`,
+          ...(selected.client_ja3 ? [`Client JA3: ${selected.client_ja3}`] : []),
+          ...(selected.client_ja4 ? [`Client JA4: ${selected.client_ja4}`] : []),
+          ...(selected.replay_target ? [`Replay Target: ${selected.replay_target}`] : []),
+          ...(selected.replay_of ? [`Replay Of: ${selected.replay_of}`] : []),
+          ...(selected.mapped_from ? [`Mapped From: ${selected.mapped_from}`] : []),
+          ...(selected.mocked ? ["Mocked: true"] : []),
+          ...(selected.streamed ? ["Streamed: response body was relayed as events"] : []),
+          ...(selected.graphql_operation_type
+            ? [`GraphQL: ${selected.graphql_operation_type} ${selected.graphql_operation_name ?? "(anonymous)"}`]
+            : []),
+          ...(selected.tags?.length ? [`Tags: ${selected.tags.join(", ")}`] : []),
+          ...(selected.notes ?? []).map((note) => `Note: ${note}`),
+          ...(selected.replay_edits ?? []).map(
+            (edit) => `Edited ${edit.field}: ${edit.original ?? "(none)"} -> ${edit.modified ?? "(removed)"}`
+          ),
+          ...(selected.original_user_agent ? [`Original User-Agent: ${selected.original_user_agent}`] : []),
+          ...(selected.tls_handshake
+            ? [
+                `TLS: ${selected.tls_handshake.tls_version ?? "?"} SNI=${selected.tls_handshake.sni ?? "-"} ALPN=${
+                  selected.tls_handshake.alpn ?? "-"
+                } Cipher=${selected.tls_handshake.cipher ?? "-"}`,
+                ...(selected.tls_handshake.cert_subject ? [`Certificate: ${selected.tls_handshake.cert_subject}`] : []),
+                ...(selected.tls_handshake.cert_issuer ? [`Issuer: ${selected.tls_handshake.cert_issuer}`] : []),
+                ...(selected.tls_handshake.cert_not_after
+                  ? [`Expires: ${new Date(selected.tls_handshake.cert_not_after * 1000).toISOString()}`]
+                  : []),
+              ]
+            : []),
           "",
           selected.request_body || "No request body",
         ].join("\n")
       : [
           `Status: ${selected.status_code || "--"}`,
           selected.error ? `Error: ${selected.error}` : "",
+          selected.client_outcome ? `Client Outcome: ${selected.client_outcome}` : "",
           `Response Body Size: ${selected.response_body_size}`,
+          selected.response_body_decoded_size
+            ? `Response Body Decoded Size: ${selected.response_body_decoded_size} (${Math.round(
+                (1 - selected.response_body_size / selected.response_body_decoded_size) * 100,
+              )}% saved)`
+            : "",
           `Response Body Truncated: ${selected.response_body_truncated}`,
           "",
           selected.response_body || "No response body",
@@ -712,20 +919,44 @@ function App() {
               <span>Listen Port</span>
               <input value={port} onChange={(event) => setPort(event.target.value)} />
             </label>
+            <label className="inline-field">
+              <input type="checkbox" checked={http3} onChange={(event) => setHttp3(event.target.checked)} />
+              <span>HTTP/3</span>
+            </label>
+            <label
+              className="inline-field"
+              title={lanAddresses.length ? `Point devices at ${lanAddresses.join(" or ")} port ${activeProxyPort ?? port}` : undefined}
+            >
+              <input type="checkbox" checked={lanMode} onChange={(event) => void handleLanMode(event.target.checked)} />
+              <span>LAN</span>
+            </label>
+            <label className="inline-field">
+              <span>Transparent</span>
+              <input
+                value={transparentProcesses}
+                placeholder="app.exe, 1234"
+                title="Processes captured even if they ignore proxy settings (asks for administrator rights)"
+                onChange={(event) => setTransparentProcesses(event.target.value)}
+              />
+            </label>
             <label className="inline-field browser-select-field">
               <span>Browser</span>
               <select value={browserTarget} onChange={(event) => setBrowserTarget(event.target.value as BrowserTarget)}>
-                <option value="edge">Edge</option>
-                <option value="chrome">Chrome</option>
-                <option value="firefox">Firefox</option>
-                <option value="brave">Brave</option>
+                {(installedBrowsers && installedBrowsers.length > 0
+                  ? installedBrowsers
+                  : (Object.keys(BROWSER_LABELS) as BrowserTarget[])
+                ).map((id) => (
+                  <option key={id} value={id}>
+                    {BROWSER_LABELS[id]}
+                  </option>
+                ))}
               </select>
             </label>
             <div className="toolbar-actions">
               <button
                 className="btn primary"
                 onClick={startCapture}
-                disabled={proxyState === "starting" || proxyState === "running"}
+                disabled={proxyState === "starting" || isProxyActive(proxyState)}
               >
                 {proxyState === "starting" ? "Starting..." : "Start Capture"}
               </button>
@@ -735,7 +966,7 @@ function App() {
               <button
                 className="btn"
                 onClick={togglePause}
-                disabled={proxyState !== "running" && proxyState !== "paused"}
+                disabled={!isProxyActive(proxyState) && proxyState !== "paused"}
               >
                 {paused ? "Resume" : "Pause"}
               </button>
@@ -746,7 +977,7 @@ function App() {
               <span className={`capture-indicator ${isCaptureOn ? "on" : "off"}`} aria-label={isCaptureOn ? "Capture on" : "Capture off"} />
               <div className="status">{statusText}</div>
             </div>
-            <button className="btn about-btn" onClick={() => setShowAbout(true)} title="About PacketLens">
+            <button className="btn about-btn" onClick={openAbout} title="About PacketLens">
               <img src={appIcon} alt="" aria-hidden className="about-icon" />
               About
             </button>
@@ -782,10 +1013,18 @@ function App() {
             <span className="icon-swatch" aria-hidden />
             Uninstall Cert
           </button>
+          <button className="btn grid-btn" onClick={handleVerifyInterception}>
+            <span className="icon-swatch" aria-hidden />
+            Verify Capture
+          </button>
           <button className="btn grid-btn" onClick={handleOpenBrowser}>
             <span className="icon-swatch" aria-hidden />
             Open Browser
           </button>
+          <button className={`btn grid-btn ${systemProxy ? "active" : ""}`} onClick={handleSystemProxy}>
+            <span className="icon-swatch" aria-hidden />
+            {systemProxy ? "Restore System Proxy" : "Use As System Proxy"}
+          </button>
           <button className="btn grid-btn" onClick={handleHowToUse}>
             <span className="icon-swatch" aria-hidden />
             How To Use
@@ -872,8 +1111,18 @@ function App() {
                       <td style={{ width: columnWidths.path, minWidth: columnWidths.path }} title={record.path}>
                         <div className="cell-text">{record.path}</div>
                       </td>
-                      <td style={{ width: columnWidths.status, minWidth: columnWidths.status }} title={String(record.status_code || "--")}>
-                        <div className="cell-text">{record.status_code || "--"}</div>
+                      <td
+                        style={{ width: columnWidths.status, minWidth: columnWidths.status }}
+                        title={
+                          record.content_length_mismatch
+                            ? `${record.status_code || "--"} (body size does not match Content-Length)`
+                            : String(record.status_code || "--")
+                        }
+                      >
+                        <div className="cell-text">
+                          {record.status_code || "--"}
+                          {record.content_length_mismatch ? <span className="length-mismatch-badge">LEN</span> : null}
+                        </div>
                       </td>
                       <td style={{ width: columnWidths.size, minWidth: columnWidths.size }} title={String(record.response_body_size)}>
                         <div className="cell-text">{record.response_body_size}</div>
@@ -916,6 +1165,23 @@ function App() {
                 Response
               </button>
               <span className="details-controls-spacer" />
+              <button
+                className="btn details-btn"
+                onClick={handleReplay}
+                disabled={!selected || !isProxyActive(proxyState)}
+              >
+                Replay
+              </button>
+              {selected?.scheme === "https" ? (
+                <button
+                  className="btn details-btn"
+                  onClick={handleCopyClientHello}
+                  disabled={!selected.client_hello_b64}
+                  title={selected.client_hello_b64 ? "Copy the raw ClientHello as base64" : "ClientHello capture is off"}
+                >
+                  Copy ClientHello
+                </button>
+              ) : null}
               {(["raw", "headers", "json", "tree"] as const).map((tab) => (
                 <button
                   key={tab}
@@ -929,6 +1195,12 @@ function App() {
             <div className="details-body">
               {subTab === "raw" ? <pre>{rawDetails}</pre> : null}
               {subTab === "headers" ? <pre>{formatHeaders(selectedHeaders)}</pre> : null}
+              {subTab === "headers" && selectedTrailers.length > 0 ? (
+                <>
+                  <h4>Trailers</h4>
+                  <pre>{formatHeaders(selectedTrailers)}</pre>
+                </>
+              ) : null}
               {subTab === "json" ? <pre>{selectedJson || "Body is not valid JSON"}</pre> : null}
               {subTab === "tree" ? (
                 selectedJsonTree ? (
@@ -969,6 +1241,13 @@ function App() {
                 </a>
               </p>
               <p>Free to use for learning and day-to-day analysis.</p>
+              {sidecarInfo ? (
+                <p className="muted">
+                  mitmproxy {sidecarInfo.mitmproxy_version} on Python {sidecarInfo.python_version} (
+                  {sidecarInfo.frozen ? "bundled" : "script"}), {sidecarInfo.platform}
+                  {sidecarInfo.loaded_addons.length ? ` | Addons: ${sidecarInfo.loaded_addons.join(", ")}` : ""}
+                </p>
+              ) : null}
             </div>
             <div className="about-actions">
               <span className="about-rights">All Rights Reserved (C) 2026 Shailz25</span>
@@ -979,6 +1258,33 @@ function App() {
           </div>
         </div>
       ) : null}
+      {captureSummary ? (
+        <div className="about-overlay" onClick={() => setCaptureSummary(null)}>
+          <div className="about-modal" onClick={(event) => event.stopPropagation()}>
+            <div className="about-content">
+              <h3>Capture Summary</h3>
+              <p>
+                {captureSummary.total_flows} flow(s), {captureSummary.total_bytes} body bytes,{" "}
+                {captureSummary.error_count} error(s) in {formatDuration(captureSummary.duration_ms)}
+              </p>
+              {captureSummary.top_hosts.length ? (
+                <ul>
+                  {captureSummary.top_hosts.map((entry) => (
+                    <li key={entry.host}>
+                      {entry.host || "(unknown)"}: {entry.count}
+                    </li>
+                  ))}
+                </ul>
+              ) : null}
+            </div>
+            <div className="about-actions">
+              <button className="btn" onClick={() => setCaptureSummary(null)}>
+                Close
+              </button>
+            </div>
+          </div>
+        </div>
+      ) : null}
     </div>
   );
 }
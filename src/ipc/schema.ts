@@ -3,6 +3,123 @@ export type HeaderEntry = {
   value: string;
 };
 
+export type MultipartPart = {
+  name: string;
+  filename: string | null;
+  content_type: string | null;
+  size: number;
+  preview: string;
+};
+
+export type ProcessInfo = {
+  pid: number;
+  name?: string | null;
+  exe?: string | null;
+};
+
+export type FlowTimings = {
+  dns_ms?: number | null;
+  connect_ms?: number | null;
+  tls_ms?: number | null;
+  send_ms?: number | null;
+  ttfb_ms?: number | null;
+  download_ms?: number | null;
+};
+
+export type TlsHandshake = {
+  sni?: string | null;
+  alpn?: string | null;
+  tls_version?: string | null;
+  cipher?: string | null;
+  cert_subject?: string | null;
+  cert_issuer?: string | null;
+  cert_not_after?: number | null;
+};
+
+export type EndpointChange = {
+  endpoint: string;
+  detail: string;
+};
+
+export type ReplayTargetResult = {
+  base_url: string;
+  status: number | null;
+  error: string | null;
+};
+
+export type MapLocalRule = {
+  id?: string;
+  url_pattern: string;
+  local_path: string;
+  content_type?: string | null;
+  compress?: boolean | null;
+};
+
+export type MapRemoteRule = {
+  id?: string;
+  url_pattern: string;
+  target_url: string;
+  preserve_host?: boolean | null;
+};
+
+export type MockRule = {
+  id?: string;
+  url_pattern: string;
+  method?: string | null;
+  status: number;
+  headers?: HeaderEntry[] | null;
+  body?: string | null;
+  latency_ms?: number | null;
+  compress?: boolean | null;
+};
+
+export type FaultRule = {
+  url_pattern: string;
+  fault: "reset" | "timeout" | "http_500" | "http_503";
+};
+
+export type BreakpointRule = {
+  url_pattern: string;
+  method?: string | null;
+};
+
+export type RequestEdit = {
+  field: string;
+  original?: string | null;
+  modified?: string | null;
+};
+
+export type WebSocketFlow = {
+  flow_id: string;
+  url: string;
+  host: string;
+  started: number;
+  ended?: number | null;
+  messages_sent: number;
+  messages_received: number;
+  bytes_sent: number;
+  bytes_received: number;
+  close_code?: number | null;
+  close_reason?: string | null;
+};
+
+export type GrpcMessage = {
+  size: number;
+  json?: unknown;
+  error?: string | null;
+};
+
+export type GrpcDetail = {
+  method?: string | null;
+  request: GrpcMessage[];
+  response: GrpcMessage[];
+};
+
+export type HostCount = {
+  host: string;
+  count: number;
+};
+
 export type FlowRecord = {
   id: string;
   started: number;
@@ -11,27 +128,83 @@ export type FlowRecord = {
   method: string;
   url: string;
   host: string;
+  host_header?: string | null;
+  user_agent?: string | null;
+  original_user_agent?: string | null;
   path: string;
   scheme: string;
+  server_port?: number | null;
+  http_version?: string | null;
+  http_protocol?: "h1" | "h2" | "h3" | null;
+  stream_id?: number | null;
+  pseudo_headers?: HeaderEntry[] | null;
   status_code: number;
   request_headers: HeaderEntry[];
   response_headers: HeaderEntry[] | null;
+  response_trailers?: HeaderEntry[] | null;
   request_body_size: number;
   response_body_size: number;
+  response_body_decoded_size?: number | null;
+  content_length_mismatch?: boolean | null;
   request_body: string;
   response_body: string;
   request_body_truncated: boolean;
   response_body_truncated: boolean;
   error: string;
+  multipart_parts?: MultipartPart[] | null;
+  pinned?: boolean;
+  client_outcome?: string | null;
+  request_body_hash?: string | null;
+  response_body_hash?: string | null;
+  redirect_chain_id?: string | null;
+  redirect_index?: number | null;
+  timings?: FlowTimings | null;
+  tls_handshake?: TlsHandshake | null;
+  certificate_chain?: string[] | null;
+  client_conn_id?: string | null;
+  server_conn_id?: string | null;
+  client_ip?: string | null;
+  client_port?: number | null;
+  process?: ProcessInfo | null;
+  client_ja3?: string | null;
+  client_ja4?: string | null;
+  client_hello_b64?: string | null;
+  replay_target?: string | null;
+  replay_of?: string | null;
+  replay_edits?: RequestEdit[] | null;
+  mapped_from?: string | null;
+  mocked?: boolean;
+  streamed?: boolean;
+  grpc?: GrpcDetail | null;
+  graphql_operation_name?: string | null;
+  graphql_operation_type?: "query" | "mutation" | "subscription" | null;
+  graphql_variables?: unknown;
+  tags?: string[];
+  notes?: string[];
 };
 
-export type ProxyStatus = "starting" | "running" | "paused" | "stopped";
+export type ProxyStatus =
+  | "starting"
+  | "running"
+  | "paused"
+  | "passthrough"
+  | "record_only"
+  | "degraded"
+  | "stopped";
+
+export type BindScope = "loopback" | "all";
 
 export type ProxyStatusEvent = {
   type: "status";
   status: ProxyStatus;
   message?: string;
   port?: number;
+  bind_address?: string;
+  detail?: string;
+  proxy_port?: number;
+  ipc_port?: number;
+  http3?: boolean | null;
+  transparent_processes?: string[] | null;
 };
 
 export type ProxyErrorEvent = {
@@ -39,15 +212,318 @@ export type ProxyErrorEvent = {
   message: string;
 };
 
+export type LineOverflowEvent = {
+  type: "line_overflow";
+  skipped_bytes: number;
+  limit_bytes: number;
+};
+
+export type LastErrorEvent = {
+  type: "last_error";
+  message: string | null;
+  timestamp: number | null;
+};
+
 export type FlowEvent = {
   type: "flow";
   record: FlowRecord;
 };
 
-export type ProxyEvent = ProxyStatusEvent | ProxyErrorEvent | FlowEvent;
+export type MarkerEvent = {
+  type: "marker";
+  timestamp: number;
+  label: string;
+};
+
+export type FlowEvictedEvent = {
+  type: "flow_evicted";
+  flow_id: string;
+};
+
+export type FlowPinnedEvent = {
+  type: "flow_pinned";
+  flow_id: string;
+  pinned: boolean;
+};
+
+export type DirectConnectionHintEvent = {
+  type: "direct_connection_hint";
+  reason: string;
+  host: string | null;
+};
+
+export type ServiceStateEvent = {
+  type: "service_state";
+  service: string;
+  running: boolean;
+  address: string | null;
+};
+
+export type LanAddressesEvent = {
+  type: "lan_addresses";
+  addresses: string[];
+};
+
+export type SidecarInfoEvent = {
+  type: "sidecar_info";
+  mitmproxy_version: string;
+  python_version: string;
+  platform: string;
+  frozen?: boolean;
+  loaded_addons: string[];
+};
+
+export type MemoryUsageEvent = {
+  type: "memory_usage";
+  rss_bytes: number;
+  flow_count: number;
+  total_body_bytes: number;
+  os_rss_bytes?: number | null;
+};
+
+export type SequenceCompleteEvent = {
+  type: "sequence_complete";
+  path: string;
+  total: number;
+  succeeded: number;
+  failed: number;
+};
+
+export type DecodedBodyEvent = {
+  type: "decoded_body";
+  flow_id: string;
+  json: unknown;
+};
+
+export type HexdumpEvent = {
+  type: "hexdump";
+  flow_id: string;
+  offset: number;
+  lines: string[];
+};
+
+export type BaselineDiffEvent = {
+  type: "baseline_diff";
+  baseline_path: string;
+  added: string[];
+  removed: string[];
+  changed: EndpointChange[];
+};
+
+export type FuzzSummaryEvent = {
+  type: "fuzz_summary";
+  flow_id: string;
+  field: string;
+  total: number;
+  status_counts: Record<string, number>;
+  failed: number;
+};
+
+export type ReplayMultiSummaryEvent = {
+  type: "replay_multi_summary";
+  flow_id: string;
+  results: ReplayTargetResult[];
+  diverged: boolean;
+};
+
+export type ReplayProgressEvent = {
+  type: "replay_progress";
+  flow_id: string;
+  completed: number;
+  total: number;
+};
+
+export type ExportCompleteEvent = {
+  type: "export_complete";
+  kind: string;
+  path: string;
+  count: number;
+  redacted?: number | null;
+};
+
+export type CaptureSummaryEvent = {
+  type: "capture_summary";
+  total_flows: number;
+  total_bytes: number;
+  error_count: number;
+  top_hosts: HostCount[];
+  duration_ms: number;
+};
+
+export type ScriptNotificationEvent = {
+  type: "script_notification";
+  flow_id: string;
+  script: string;
+  message: string;
+};
+
+export type WebSocketMessageEvent = {
+  type: "websocket_message";
+  flow_id: string;
+  direction: "client" | "server";
+  opcode: number;
+  payload: string;
+  timestamp: number;
+  truncated?: boolean;
+};
+
+export type WebSocketClosedEvent = {
+  type: "websocket_closed";
+  summary: WebSocketFlow;
+};
+
+export type StreamChunkEvent = {
+  type: "stream_chunk";
+  flow_id: string;
+  sequence: number;
+  data: string;
+  timestamp: number;
+};
+
+export type InterceptedEvent = {
+  type: "intercepted";
+  flow_id: string;
+  method: string;
+  url: string;
+  request_headers: HeaderEntry[];
+  request_body?: string | null;
+};
+
+export type ProxyEvent =
+  | ProxyStatusEvent
+  | ProxyErrorEvent
+  | LineOverflowEvent
+  | LastErrorEvent
+  | FlowEvent
+  | MarkerEvent
+  | FlowEvictedEvent
+  | FlowPinnedEvent
+  | DirectConnectionHintEvent
+  | ServiceStateEvent
+  | LanAddressesEvent
+  | SidecarInfoEvent
+  | MemoryUsageEvent
+  | SequenceCompleteEvent
+  | DecodedBodyEvent
+  | HexdumpEvent
+  | BaselineDiffEvent
+  | FuzzSummaryEvent
+  | ReplayMultiSummaryEvent
+  | ReplayProgressEvent
+  | ExportCompleteEvent
+  | CaptureSummaryEvent
+  | WebSocketMessageEvent
+  | WebSocketClosedEvent
+  | StreamChunkEvent
+  | InterceptedEvent
+  | ScriptNotificationEvent;
 
 export type ProxyCommand =
-  | { type: "start"; port: number }
-  | { type: "stop" }
+  | {
+      type: "start";
+      port: number;
+      bind_scope?: BindScope;
+      confirm_lan?: boolean;
+      auto_port?: boolean;
+      http3?: boolean;
+      transparent_processes?: string[];
+    }
+  | { type: "stop"; skip_summary?: boolean }
   | { type: "pause" }
-  | { type: "resume" };
+  | { type: "resume" }
+  | { type: "passthrough"; enabled: boolean }
+  | { type: "mute_hosts"; patterns: string[] }
+  | { type: "unmute_hosts"; patterns: string[] }
+  | { type: "clear" }
+  | { type: "pin_flow"; flow_id: string; pinned: boolean }
+  | { type: "mark_flow_outcome"; flow_id: string; outcome: string }
+  | { type: "clear_matching"; patterns: string[] }
+  | { type: "body_capture_toggles"; capture_request: boolean; capture_response: boolean }
+  | { type: "set_body_policy"; store_limit: number; stream_limit: number }
+  | { type: "replay_sequence"; path: string; base_url?: string | null; preserve_host?: boolean }
+  | { type: "get_last_error" }
+  | { type: "clear_last_error" }
+  | { type: "sni_override"; host: string; sni: string }
+  | { type: "set_request_timeout"; seconds: number }
+  | { type: "resolve_timings"; enabled: boolean }
+  | { type: "show_preflight"; enabled: boolean }
+  | { type: "replay_with_timing"; flow_ids: string[]; speed: number }
+  | { type: "replay_fuzz"; flow_id: string; field: string; mutations: string[] }
+  | { type: "replay_multi"; flow_id: string; base_urls: string[]; preserve_host?: boolean }
+  | { type: "send_raw"; raw_request: string; scheme: string; host: string; port: number }
+  | { type: "capture_handshake"; host: string }
+  | { type: "replay_handshake"; flow_id: string }
+  | { type: "replay_flow"; id: string }
+  | { type: "set_map_local_rules"; rules: MapLocalRule[] }
+  | { type: "set_map_remote_rules"; rules: MapRemoteRule[] }
+  | { type: "set_mock_rules"; rules: MockRule[] }
+  | { type: "set_fault_rules"; rules: FaultRule[] }
+  | { type: "set_breakpoints"; rules: BreakpointRule[] }
+  | { type: "resume_intercepted"; flow_id: string }
+  | {
+      type: "modify_intercepted";
+      flow_id: string;
+      method?: string | null;
+      url?: string | null;
+      headers?: HeaderEntry[] | null;
+      body?: string | null;
+    }
+  | { type: "drop_intercepted"; flow_id: string }
+  | {
+      type: "edit_and_replay";
+      parent_id: string;
+      method: string;
+      url: string;
+      headers?: HeaderEntry[] | null;
+      body?: string | null;
+    }
+  | {
+      type: "send_request";
+      method: string;
+      url: string;
+      headers?: HeaderEntry[] | null;
+      body?: string | null;
+      direct?: boolean | null;
+    }
+  | { type: "set_auth_refresh"; token_header: string; refresh_flow_id: string; extract_json_path: string }
+  | { type: "strip_accept_encoding"; enabled: boolean }
+  | { type: "capture_tls_fingerprints"; enabled: boolean }
+  | { type: "capture_client_hello"; enabled: boolean }
+  | { type: "port_filter"; allowed_ports: number[] }
+  | { type: "brotli_decode"; enabled: boolean }
+  | { type: "set_user_agent"; value?: string | null }
+  | { type: "connection_reuse"; enabled: boolean }
+  | { type: "record_only"; canned_status: number }
+  | { type: "throttle_hosts"; patterns: string[]; down_kbps?: number | null; latency_ms?: number | null }
+  | { type: "clear_host_throttles" }
+  | { type: "set_throttle"; download_kbps?: number | null; upload_kbps?: number | null; latency_ms?: number | null }
+  | { type: "latency_by_status"; rules: [number, number][] }
+  | { type: "clear_latency_rules" }
+  | { type: "set_dns_servers"; servers: string[] }
+  | { type: "scope_to_client"; token: string }
+  | { type: "set_process_filter"; pids?: number[]; names?: string[] }
+  | { type: "pause_on_error"; enabled: boolean; status_min?: number | null }
+  | { type: "force_http1"; hosts: string[] }
+  | { type: "dedupe_by_endpoint"; keep: "first" | "last" | "all" }
+  | { type: "add_marker"; label: string }
+  | { type: "get_sidecar_info" }
+  | { type: "get_memory_usage" }
+  | { type: "export_waterfall"; path: string }
+  | { type: "export_postman"; path: string; filter?: string | null }
+  | { type: "compare_baseline"; baseline_path: string }
+  | { type: "start_tail"; host_pattern: string; path: string }
+  | { type: "stop_tail" }
+  | { type: "set_proto_descriptor"; descriptor_path: string }
+  | { type: "decode_proto"; flow_id: string }
+  | { type: "get_hexdump"; flow_id: string; direction: "request" | "response"; offset: number; length: number }
+  | { type: "save_response_body"; flow_id: string; path: string }
+  | { type: "export_openapi"; path: string; filter?: string | null }
+  | { type: "export_session_diff"; baseline_path: string; out_path: string }
+  | { type: "export_redacted_har"; path: string; header_names: string[]; body_patterns: string[] }
+  | { type: "start_http_bridge"; port: number }
+  | { type: "stop_http_bridge" }
+  | { type: "start_metrics_endpoint"; port: number }
+  | { type: "stop_metrics_endpoint" }
+  | { type: "start_pac_server"; port: number; include?: string[]; exclude?: string[] }
+  | { type: "stop_pac_server" }
+  | { type: "get_lan_addresses" };